@@ -0,0 +1,18 @@
+// Prints every number of one training step of the 2-2-2 network from the back propagation note and Simple_Neural_Net.ods.
+// Run with: cargo run --example backprop_trace -- [text|markdown|latex]
+
+use simple_nn::spreadsheet::{example_network, EXAMPLE_INPUTS, EXAMPLE_TARGETS};
+use simple_nn::trace::TraceFormat;
+
+fn main() {
+    let format = match std::env::args().nth(1).unwrap_or("text".to_string()).as_str() {
+        "text" => TraceFormat::Text,
+        "markdown" => TraceFormat::Markdown,
+        "latex" => TraceFormat::Latex,
+        other => panic!("Unknown format {}, use text, markdown or latex", other),
+    };
+
+    let mut network = example_network(); // The starting weights of the spreadsheet
+    let trace = network.trace_make_adjust(&EXAMPLE_INPUTS, &EXAMPLE_TARGETS).unwrap();
+    print!("{}", trace.render(format));
+}
@@ -0,0 +1,42 @@
+// Writes a network as Rust or C source code with a predict function that needs nothing else, e.g. for a microcontroller.
+// Run with: cargo run --example codegen -- [saved network] [rust|c] [output file]
+// e.g. cargo run --example codegen -- checkpoints/checkpoint_1600.txt c seven_segment.c
+// Without a saved network, a 4-8-8-9 network made from a fixed seed is trained on the 7-segment task first.
+// Without an output file, the code is saved as target/network.rs or target/network.c.
+
+use std::fs;
+use std::path::Path;
+
+use simple_nn::checkpoint::load_network;
+use simple_nn::codegen::{save_code, Language};
+use simple_nn::dataset;
+use simple_nn::nodes_layers::Network;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let network = match args.first() {
+        Some(path) if path != "-" => load_network(Path::new(path)).unwrap(),
+        _ => {
+            let data = dataset::seven_segment();
+            let mut network = Network::with_seed(vec![4, 8, 8, 9], 0.5, 1);
+            for _ in 0..2_000 {
+                for number in 0..data.len() {
+                    network.find_make_adjust(&data.inputs[number], &data.outputs[number]);
+                }
+            }
+            network
+        },
+    };
+    let language = match args.get(1).map(|text| text.as_str()).unwrap_or("rust") {
+        "rust" => Language::Rust,
+        "c" => Language::C,
+        other => panic!("Unknown language {}, use rust or c", other),
+    };
+    let default_output = if language == Language::Rust {"target/network.rs"} else {"target/network.c"};
+    let output = args.get(2).cloned().unwrap_or_else(|| default_output.to_string());
+    if let Some(folder) = Path::new(&output).parent() {
+        fs::create_dir_all(folder).unwrap();
+    }
+    save_code(&network, language, Path::new(&output)).unwrap();
+    println!("Saved {}", output);
+}
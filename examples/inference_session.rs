@@ -0,0 +1,82 @@
+// Checks that an InferenceSession gives the same outputs as Network::calculate without allocating, and compares their speed.
+// Run with: cargo run --release --example inference_session
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use simple_nn::conv::{Conv2d, Flatten, MaxPool2d};
+use simple_nn::modules::{ActivationLayer, Dropout, Normalization, Softmax};
+use simple_nn::nodes_layers::{Activation, Layer, Network};
+use simple_nn::rng::SimpleRng;
+use simple_nn::session::InferenceSession;
+
+// Counts every allocation the program makes.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn check(name: &str, network: &Network, input_count: usize, calls: usize) {
+    let mut rng = SimpleRng::new(1);
+    let samples: Vec<Vec<f32>> = (0..calls).map(|_| (0..input_count).map(|_| rng.gen_f32()).collect()).collect();
+    let mut session = InferenceSession::new(network, input_count);
+    let mut outputs = vec![0.0; session.output_count()];
+
+    let start = Instant::now();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    for inputs in samples.iter() {
+        session.forward_into(inputs, &mut outputs);
+    }
+    let session_allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let session_time = start.elapsed();
+
+    let start = Instant::now();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    for inputs in samples.iter() {
+        network.calculate(inputs);
+    }
+    let calculate_allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let calculate_time = start.elapsed();
+
+    for inputs in samples.iter().take(100) {
+        session.forward_into(inputs, &mut outputs);
+        let values = network.calculate(inputs);
+        if outputs.to_vec() != values[values.len() - 1] || *session.values() != values || session.run(inputs) != &values[values.len() - 1][..] {
+            panic!("The session and calculate give different values for {}", name);
+        }
+    }
+    println!("{}: {} calls, forward_into made {} allocations in {:?}, calculate made {} in {:?}",
+        name, calls, session_allocations, session_time, calculate_allocations, calculate_time);
+}
+
+fn main() {
+    let network = Network::with_seed(vec![4, 8, 8, 9], 0.5, 1);
+    check("4-8-8-9", &network, 4, 100_000);
+
+    let mut network = Network::empty(0.1);
+    network.push(Box::new(Conv2d::new(1, 4, (8, 8), 3, 1, 1)));
+    network.push(Box::new(ActivationLayer::new(Activation::Relu)));
+    network.push(Box::new(MaxPool2d::new(4, (8, 8), 2, 2, 0)));
+    network.push(Box::new(Flatten::new(vec![4, 4, 4])));
+    network.push(Box::new(Layer::with_activation(64, 16, Activation::Identity)));
+    network.push(Box::new(Normalization::new(16)));
+    network.push(Box::new(ActivationLayer::new(Activation::Tanh)));
+    network.push(Box::new(Dropout::new(0.2)));
+    network.push(Box::new(Layer::with_activation(16, 10, Activation::Identity)));
+    network.push(Box::new(Softmax::new()));
+    check("convolution", &network, 64, 10_000);
+}
@@ -0,0 +1,70 @@
+// Trains a 784-hidden-10 network on the MNIST handwritten digits and reports the accuracy on the test images.
+// The four unpacked MNIST files have to be in a local directory, nothing is downloaded.
+// Run with: cargo run --release --example mnist -- [directory] [epochs] [hidden nodes]
+// e.g. cargo run --release --example mnist -- mnist 3 100
+
+use std::path::Path;
+
+use simple_nn::dataset::Dataset;
+use simple_nn::idx::load_mnist;
+use simple_nn::modules::Softmax;
+use simple_nn::nodes_layers::{Activation, Cost, Layer, Network};
+use simple_nn::rng::SimpleRng;
+
+fn load(directory: &Path, images: &str, labels: &str) -> Dataset {
+    // Some copies of MNIST use a dot instead of a dash before "idx", e.g. train-images.idx3-ubyte
+    let find = |name: &str| {
+        let path = directory.join(name);
+        if path.exists() {path} else {directory.join(name.replacen("-idx", ".idx", 1))}
+    };
+    match load_mnist(&find(images), &find(labels)) {
+        Ok(data) => data,
+        Err(error) => panic!("Couldn't read the MNIST files in {}: {}", directory.display(), error),
+    }
+}
+
+fn accuracy(network: &Network, data: &Dataset) -> f32 {
+    let mut correct = 0;
+    for num in 0..data.len() {
+        let (class, _) = network.predict_class(&data.inputs[num]);
+        if data.outputs[num][class] == 1.0 {
+            correct += 1;
+        }
+    }
+    correct as f32 / data.len() as f32
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let directory = Path::new(args.first().map(|arg| arg.as_str()).unwrap_or("mnist")).to_path_buf();
+    let epochs: usize = args.get(1).map(|arg| arg.parse().expect("The number of epochs should be a whole number")).unwrap_or(3);
+    let hidden: usize = args.get(2).map(|arg| arg.parse().expect("The number of hidden nodes should be a whole number")).unwrap_or(100);
+
+    let training = load(&directory, "train-images-idx3-ubyte", "train-labels-idx1-ubyte");
+    let test = load(&directory, "t10k-images-idx3-ubyte", "t10k-labels-idx1-ubyte");
+    println!("{} training images and {} test images", training.len(), test.len());
+
+    let mut rng = SimpleRng::new(1);
+    let mut network = Network::empty(0.05);
+    network.push(Box::new(Layer::from_rng(784, hidden, Activation::Sigmoid, &mut rng)));
+    network.push(Box::new(Layer::from_rng(hidden, 10, Activation::Identity, &mut rng)));
+    network.push(Box::new(Softmax::new()));
+    network.cost = Cost::CrossEntropy;
+
+    let mut order: Vec<usize> = (0..training.len()).collect();
+    for epoch in 0..epochs {
+        for num in (1..order.len()).rev() { // Shuffles the training images, so each epoch sees them in a different order
+            order.swap(num, rng.gen_index(num + 1));
+        }
+        let mut cost = 0.0;
+        for (step, num) in order.iter().enumerate() {
+            cost += network.find_cost(&training.inputs[*num], &training.outputs[*num]);
+            network.find_make_adjust(&training.inputs[*num], &training.outputs[*num]);
+            if (step + 1) % 10_000 == 0 {
+                println!("epoch: {} images: {} cost: {:.4}", epoch + 1, step + 1, cost / 10_000.0);
+                cost = 0.0;
+            }
+        }
+        println!("epoch: {} test accuracy: {:.2}%", epoch + 1, accuracy(&network, &test) * 100.0);
+    }
+}
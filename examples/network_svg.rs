@@ -0,0 +1,30 @@
+// Draws a network as an SVG image, without opening a window.
+// Run with: cargo run --example network_svg -- [saved network] [inputs] [output file]
+// e.g. cargo run --example network_svg -- checkpoints/checkpoint_1600.txt 0,1,0,1 network.svg
+// Without a saved network, a new 4-8-8-9 network made from a fixed seed is drawn.
+// Without an output file, the image is saved as target/network.svg.
+
+use std::fs;
+use std::path::Path;
+
+use simple_nn::checkpoint::load_network;
+use simple_nn::nodes_layers::Network;
+use simple_nn::svg::save_network_svg;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let network = match args.first() {
+        Some(path) if path != "-" => load_network(Path::new(path)).unwrap(),
+        _ => Network::with_seed(vec![4, 8, 8, 9], 0.5, 1),
+    };
+    let inputs: Vec<f32> = match args.get(1) {
+        Some(text) => text.split(',').map(|value| value.trim().parse().expect("The inputs should be numbers separated by commas")).collect(),
+        None => vec![0.0, 1.0, 0.0, 1.0],
+    };
+    let output = args.get(2).cloned().unwrap_or_else(|| "target/network.svg".to_string());
+    if let Some(folder) = Path::new(&output).parent() {
+        fs::create_dir_all(folder).unwrap();
+    }
+    save_network_svg(&network, &inputs, Path::new(&output), 700.0, 500.0).unwrap();
+    println!("Saved {}", output);
+}
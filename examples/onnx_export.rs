@@ -0,0 +1,45 @@
+// Exports a network to ONNX, reads the file back in, and checks that it gives the same outputs as the network.
+// Run with: cargo run --example onnx_export -- [saved network] [output file]
+// e.g. cargo run --example onnx_export -- checkpoints/checkpoint_1600.txt network.onnx
+// Without a saved network, a new 4-8-8-9 network made from a fixed seed is exported.
+// Without an output file, the network is saved as target/network.onnx.
+
+use std::fs;
+use std::path::Path;
+
+use simple_nn::checkpoint::load_network;
+use simple_nn::dataset;
+use simple_nn::nodes_layers::Network;
+use simple_nn::onnx::{check_round_trip, load_onnx, save_onnx};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let network = match args.first() {
+        Some(path) if path != "-" => load_network(Path::new(path)).unwrap(),
+        _ => Network::with_seed(vec![4, 8, 8, 9], 0.5, 1),
+    };
+    let output = args.get(1).cloned().unwrap_or_else(|| "target/network.onnx".to_string());
+    if let Some(folder) = Path::new(&output).parent() {
+        fs::create_dir_all(folder).unwrap();
+    }
+    save_onnx(&network, Path::new(&output)).unwrap();
+
+    let model = load_onnx(Path::new(&output)).unwrap();
+    let operators: Vec<&str> = model.nodes.iter().map(|node| node.op_type.as_str()).collect();
+    println!("Saved {} with the operators {}", output, operators.join(", "));
+
+    // All the binary inputs, if there are few enough, else the seven segment inputs padded with zeros.
+    let input_size = network.layers.iter().find_map(|module| module.as_layer()).unwrap().nodes[0].weights.len();
+    let samples = if input_size <= 10 {dataset::binary_inputs(input_size)} else {
+        dataset::seven_segment().inputs.iter().map(|inputs| {
+            let mut padded = inputs.clone();
+            padded.resize(input_size, 0.0);
+            padded
+        }).collect()
+    };
+    let difference = check_round_trip(&network, &samples).unwrap();
+    println!("Largest difference between the network and the file for {} inputs: {}", samples.len(), difference);
+    if difference > 1e-5 {
+        panic!("The exported network gives different outputs");
+    }
+}
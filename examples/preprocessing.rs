@@ -0,0 +1,62 @@
+// Trains on table data with very different scales, a category column and missing values, using a Preprocessor.
+// The preprocessor is fitted on the training rows only, then saved with the network and loaded again for the test rows.
+// Run with: cargo run --example preprocessing
+
+use std::path::Path;
+
+use simple_nn::nodes_layers::Network;
+use simple_nn::preprocess::{load_model, save_model, Column, Preprocessor, Scaling};
+use simple_nn::rng::SimpleRng;
+
+fn main() {
+    // Made-up houses: floor area in square meters, year built, heating (0 = gas, 1 = electric, 2 = district heating) and number of rooms.
+    // The output is whether the house sells for more than average. Some values are missing, written as NaN.
+    let mut rng = SimpleRng::new(7);
+    let mut rows = Vec::new();
+    let mut outputs = Vec::new();
+    for _ in 0..400 {
+        let area = rng.gen_range(40.0, 250.0);
+        let year = rng.gen_range(1900.0, 2020.0).floor();
+        let heating = rng.gen_index(3) as f32;
+        let rooms = (area / 30.0).floor() + rng.gen_index(2) as f32;
+        let score = (area - 140.0) / 60.0 + (year - 1960.0) / 40.0 + if heating == 2.0 {0.5} else {0.0};
+        let mut row = vec![area, year, heating, rooms];
+        if rng.gen_f32() < 0.1 {
+            let missing = rng.gen_index(4);
+            row[missing] = f32::NAN;
+        }
+        rows.push(row);
+        outputs.push(vec![if score > 0.0 {1.0} else {0.0}]);
+    }
+    let (training_rows, test_rows) = rows.split_at(300);
+    let (training_outputs, test_outputs) = outputs.split_at(300);
+
+    let columns = vec![Column::new(Scaling::ZScore), Column::new(Scaling::MinMax), Column::new(Scaling::OneHot), Column::new(Scaling::ZScore)];
+    let preprocessor = Preprocessor::fit(training_rows, &columns);
+    let mut network = Network::with_seed(vec![preprocessor.output_size(), 8, 1], 0.5, 1);
+    for epoch in 0..200 {
+        let mut cost = 0.0;
+        for num in 0..training_rows.len() {
+            let inputs = preprocessor.transform(&training_rows[num]);
+            cost += network.find_cost(&inputs, &training_outputs[num]);
+            network.find_make_adjust(&inputs, &training_outputs[num]);
+        }
+        if epoch % 50 == 0 {
+            println!("epoch: {} cost: {:.4}", epoch, cost / training_rows.len() as f32);
+        }
+    }
+
+    let path = std::env::temp_dir().join("simple_nn_houses.txt");
+    save_model(&preprocessor, &network, &path).unwrap();
+    let (preprocessor, network) = load_model(Path::new(&path)).unwrap();
+    println!("Saved and loaded {}", path.display());
+
+    let mut correct = 0;
+    for num in 0..test_rows.len() {
+        let values = network.calculate(&preprocessor.transform(&test_rows[num]));
+        if (values[values.len() - 1][0] > 0.5) == (test_outputs[num][0] > 0.5) {
+            correct += 1;
+        }
+    }
+    println!("{} of {} test houses are classified correctly", correct, test_rows.len());
+}
@@ -0,0 +1,53 @@
+// Trains a few networks, quantizes them to 8 bit integers, and reports how much the accuracy drops.
+// Run with: cargo run --release --example quantize
+
+use simple_nn::dataset::{self, Dataset};
+use simple_nn::nodes_layers::{Activation, Layer, Network};
+use simple_nn::quantize::{compare, quantize, Granularity};
+use simple_nn::rng::SimpleRng;
+
+fn train(network: &mut Network, data: &Dataset, epochs: usize) {
+    for _ in 0..epochs {
+        for number in 0..data.len() {
+            network.find_make_adjust(&data.inputs[number], &data.outputs[number]);
+        }
+    }
+}
+
+fn report(name: &str, network: &Network, data: &Dataset) {
+    for granularity in [Granularity::PerLayer, Granularity::PerChannel].iter() {
+        let quantized = quantize(network, &data.inputs, *granularity).unwrap();
+        let report = compare(network, &quantized, data);
+        println!("{:>13}, {:?}: accuracy {:.1}% -> {:.1}% (drop {:.1} points), largest output difference {:.4}, mean {:.4}, {} -> {} bytes",
+            name, granularity, 100.0 * report.float_accuracy, 100.0 * report.quantized_accuracy, 100.0 * report.accuracy_drop,
+            report.largest_difference, report.mean_difference, report.float_size, report.quantized_size);
+    }
+}
+
+fn main() {
+    let seven_segment = dataset::seven_segment();
+    let mut network = Network::with_seed(vec![4, 8, 8, 9], 0.5, 1);
+    train(&mut network, &seven_segment, 2_000);
+    report("seven_segment", &network, &seven_segment);
+
+    let decimal = dataset::binary_to_decimal();
+    let mut network = Network::with_seed(vec![4, 16, 16, 16], 0.5, 2);
+    train(&mut network, &decimal, 3_000);
+    report("decimal", &network, &decimal);
+
+    // Calibrated on the training points and tested on new ones.
+    let mut rng = SimpleRng::new(3);
+    let train_data = dataset::generate("moons", 400, &mut rng).unwrap();
+    let test_data = dataset::generate("moons", 400, &mut rng).unwrap();
+    let mut network = Network::empty(0.05);
+    network.push(Box::new(Layer::from_rng(2, 16, Activation::Relu, &mut rng)));
+    network.push(Box::new(Layer::from_rng(16, 16, Activation::Tanh, &mut rng)));
+    network.push(Box::new(Layer::from_rng(16, 1, Activation::Sigmoid, &mut rng)));
+    train(&mut network, &train_data, 300);
+    for granularity in [Granularity::PerLayer, Granularity::PerChannel].iter() {
+        let quantized = quantize(&network, &train_data.inputs, *granularity).unwrap();
+        let report = compare(&network, &quantized, &test_data);
+        println!("{:>13}, {:?}: accuracy {:.1}% -> {:.1}% (drop {:.1} points) on new points",
+            "moons", granularity, 100.0 * report.float_accuracy, 100.0 * report.quantized_accuracy, 100.0 * report.accuracy_drop);
+    }
+}
@@ -0,0 +1,55 @@
+// Trains a recurrent network that reads the 4 input bits one per step and outputs the 7-segment pattern of the number.
+// Run with: cargo run --example recurrent_seven_segment -- [elman|gru|lstm]
+
+use simple_nn::dataset;
+use simple_nn::nodes_layers::{Layer, Network};
+use simple_nn::recurrent::{ElmanCell, GruCell, LstmCell, RecurrentCell, RecurrentNetwork};
+
+fn main() {
+    let data = dataset::seven_segment();
+
+    // The bits of each number, most significant first, one bit per step.
+    let mut sequences = Vec::new();
+    for number in 0..16 {
+        let mut sequence = Vec::new();
+        for bit in data.inputs[number].iter() {
+            sequence.push(vec![*bit]);
+        }
+        sequences.push(sequence);
+    }
+
+    let hidden_size = 16;
+    let kind = std::env::args().nth(1).unwrap_or("gru".to_string());
+    let cell: Box<dyn RecurrentCell> = match kind.as_str() {
+        "elman" => Box::new(ElmanCell::new(1, hidden_size)),
+        "gru" => Box::new(GruCell::new(1, hidden_size)),
+        "lstm" => Box::new(LstmCell::new(1, hidden_size)),
+        _ => panic!("Unknown cell {}, use elman, gru or lstm", kind),
+    };
+    let mut output = Network::empty(0.5);
+    output.push(Box::new(Layer::new(hidden_size, 9)));
+    let mut network = RecurrentNetwork::new(cell, output, 4);
+
+    for epoch in 0..3_000 {
+        let mut cost = 0.0;
+        for (sequence, desired_outputs) in sequences.iter().zip(data.outputs.iter()) {
+            network.find_make_adjust(sequence, desired_outputs);
+            cost += network.find_cost(sequence, desired_outputs);
+        }
+        if epoch % 250 == 0 {
+            println!("epoch: {:?} cost: {:?}", epoch, cost / 16.0);
+        }
+    }
+
+    let mut correct = 0;
+    for (number, sequence) in sequences.iter().enumerate() {
+        let values = network.calculate(sequence);
+        let outputs = &values[values.len() - 1];
+        let right = outputs.iter().zip(data.outputs[number].iter()).all(|(output, desired)| (*output > 0.5) == (*desired > 0.5));
+        if right {
+            correct += 1;
+        }
+        println!("{:2}: {}", number, if right {"correct"} else {"wrong"});
+    }
+    println!("{} of 16 numbers are shown correctly", correct);
+}
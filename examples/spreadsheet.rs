@@ -0,0 +1,33 @@
+// Writes training steps of a network as a spreadsheet in the layout of Simple_Neural_Net.ods, to check the program's numbers cell by cell.
+// Run with: cargo run --example spreadsheet -- [saved network] [inputs] [targets] [steps] [output name]
+// e.g. cargo run --example spreadsheet -- checkpoints/checkpoint_1600.txt 0,1,0,1 0,0,1,0,1,1,0,1,1 20
+// Without a saved network, the 2-2-2 network of Simple_Neural_Net.ods is used, with the same starting weights, inputs and targets.
+// The results are saved as [output name].fods, which LibreOffice opens, and [output name].csv, by default target/worked_example.fods and target/worked_example.csv.
+
+use std::fs;
+use std::path::Path;
+
+use simple_nn::checkpoint::load_network;
+use simple_nn::spreadsheet::{example_network, save_csv, save_fods, EXAMPLE_INPUTS, EXAMPLE_TARGETS};
+
+fn numbers(text: &str) -> Vec<f32> {
+    text.split(',').map(|value| value.trim().parse().expect("The values should be numbers separated by commas")).collect()
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (network, inputs, targets) = match args.first() {
+        Some(path) => (load_network(Path::new(path)).unwrap(), numbers(&args[1]), numbers(&args[2])),
+        None => (example_network(), EXAMPLE_INPUTS.to_vec(), EXAMPLE_TARGETS.to_vec()),
+    };
+    let steps = args.get(3).map(|steps| steps.parse().expect("The number of steps should be a whole number")).unwrap_or(72);
+    let output = args.get(4).cloned().unwrap_or_else(|| "target/worked_example".to_string());
+    if let Some(folder) = Path::new(&output).parent() {
+        fs::create_dir_all(folder).unwrap();
+    }
+    let fods_path = format!("{}.fods", output);
+    let csv_path = format!("{}.csv", output);
+    save_fods(&network, &inputs, &targets, steps, Path::new(&fods_path)).unwrap();
+    save_csv(&network, &inputs, &targets, steps, Path::new(&csv_path)).unwrap();
+    println!("Saved {} training steps to {} and {}", steps, fods_path, csv_path);
+}
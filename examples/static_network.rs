@@ -0,0 +1,42 @@
+// Trains the 4-8-8-9 network of the 7-segment task, turns it into a StaticNetwork, and checks that both give the same outputs.
+// Run with: cargo run --example static_network
+
+use simple_nn::dataset;
+use simple_nn::nodes_layers::Network;
+use simple_nn::static_network::StaticNetwork;
+
+fn main() {
+    let data = dataset::seven_segment();
+    let mut network = Network::with_seed(vec![4, 8, 8, 9], 0.5, 1);
+
+    // The same seed gives the same weights in both kinds of network.
+    let untrained: StaticNetwork<4, 8, 8, 9> = StaticNetwork::with_seed(1);
+    if StaticNetwork::from_network(&network) != Some(untrained) {
+        panic!("The seeded networks should be the same");
+    }
+
+    for _ in 0..2_000 {
+        for number in 0..data.len() {
+            network.find_make_adjust(&data.inputs[number], &data.outputs[number]);
+        }
+    }
+
+    // The sizes are part of the type, so e.g. StaticNetwork<4, 8, 8, 7> would give None here.
+    let fixed: StaticNetwork<4, 8, 8, 9> = StaticNetwork::from_network(&network).unwrap();
+    for number in 0..data.len() {
+        let inputs = [data.inputs[number][0], data.inputs[number][1], data.inputs[number][2], data.inputs[number][3]];
+        let outputs = fixed.calculate(&inputs);
+        let values = network.calculate(&data.inputs[number]);
+        if outputs.to_vec() != values[values.len() - 1] {
+            panic!("The outputs for {} are different", number);
+        }
+        let segments: Vec<&str> = outputs.iter().map(|value| if *value > 0.5 {"1"} else {"0"}).collect();
+        println!("{:2}: {}", number, segments.join(""));
+    }
+    println!("The StaticNetwork gives the same outputs as the Network for all 16 numbers");
+
+    let back = fixed.to_network(0.5);
+    if StaticNetwork::from_network(&back) != Some(fixed) {
+        panic!("Converting back should give the same network");
+    }
+}
@@ -0,0 +1,409 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::conv::{AvgPool2d, Conv1d, Conv2d, Flatten, MaxPool2d};
+use crate::modules::{ActivationLayer, Dropout, Normalization, Reshape, Softmax};
+use crate::nodes_layers::{Activation, Cost, Layer, Module, Network};
+use crate::rng::SimpleRng;
+
+//Checkpoints and saved networks are plain text, one "key values" pair per line, so they can be read and compared by hand.
+//Numbers are written with Rust's shortest exact formatting, so a loaded network gives exactly the same results as the saved one.
+//
+//simple_nn network 1
+//learning_rate 0.5
+//cost squared_error
+//rng 1234567890
+//modules 3
+//module layer 4 8 sigmoid
+//parameters 0.1 -0.3 ...
+//...
+//
+//A checkpoint is the same, starting with "simple_nn checkpoint 1", a line for each training counter, and the name, seed and size
+//of the generated dataset before the network, so a resumed run trains on exactly the same samples.
+//There is no optimizer state or learning rate schedule to save, since the network is trained with a fixed learning rate.
+
+const NETWORK_HEADER: &str = "simple_nn network 1";
+const CHECKPOINT_HEADER: &str = "simple_nn checkpoint 1";
+const MAX_MODULE_PARAMETERS: usize = 1 << 26; //A saved module with more parameters than this is taken as a corrupt file, rather than allocated.
+
+#[derive(Clone)]
+pub struct Checkpoint {
+//Everything needed to continue a training run exactly where it was stopped.
+    pub network: Network,
+    pub time: usize, //The number of training steps so far.
+    pub relevant_data: usize, //The training sample that is used next.
+    pub dataset: String, //The name given to dataset::generate, e.g. xor or spirals.
+    pub data_seed: u64, //The seed of the SimpleRng the dataset was generated with.
+    pub samples: usize, //The number of samples asked of dataset::generate.
+}
+
+impl Checkpoint {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = format!("{}\ntime {}\nrelevant_data {}\n", CHECKPOINT_HEADER, self.time, self.relevant_data);
+        text.push_str(&format!("dataset {}\ndata_seed {}\nsamples {}\n", self.dataset, self.data_seed, self.samples));
+        text.push_str(&network_to_text(&self.network));
+        fs::write(path, text)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Checkpoint> {
+        let text = fs::read_to_string(path)?;
+        let mut lines = Lines::new(&text);
+        if lines.next_line()? != CHECKPOINT_HEADER {
+            return Err(invalid(format!("{} isn't a checkpoint", path.display())))
+        }
+        let time = parse(&lines.value("time")?)?;
+        let relevant_data = parse(&lines.value("relevant_data")?)?;
+        let dataset = lines.value("dataset")?;
+        if dataset.is_empty() || dataset.contains(char::is_whitespace) {
+            return Err(invalid(format!("{:?} isn't a dataset name", dataset)))
+        }
+        let data_seed = parse(&lines.value("data_seed")?)?;
+        let samples = parse(&lines.value("samples")?)?;
+        let network = read_network(&mut lines)?;
+        Ok(Checkpoint {
+            network,
+            time,
+            relevant_data,
+            dataset,
+            data_seed,
+            samples,
+        })
+    }
+}
+
+pub fn save_network(network: &Network, path: &Path) -> io::Result<()> {
+//Saves a network on its own, without the training counters of a checkpoint.
+    fs::write(path, format!("{}\n{}", NETWORK_HEADER, network_to_text(network)))
+}
+
+pub fn load_network(path: &Path) -> io::Result<Network> {
+//Loads a network saved with save_network, or the network of a checkpoint.
+    let text = fs::read_to_string(path)?;
+    let mut lines = Lines::new(&text);
+    let header = lines.next_line()?;
+    if header == CHECKPOINT_HEADER {
+        //The training counters and the dataset come before the network. Only the network is needed.
+        lines.value("time")?;
+        lines.value("relevant_data")?;
+        lines.value("dataset")?;
+        lines.value("data_seed")?;
+        lines.value("samples")?;
+    } else if header != NETWORK_HEADER {
+        return Err(invalid(format!("{} isn't a saved network", path.display())))
+    }
+    read_network(&mut lines)
+}
+
+pub fn network_to_text(network: &Network) -> String {
+    let mut text = String::new();
+    text.push_str(&format!("learning_rate {}\n", network.learning_rate));
+    text.push_str(&format!("cost {}\n", match network.cost {Cost::SquaredError => "squared_error", Cost::CrossEntropy => "cross_entropy"}));
+    text.push_str(&format!("rng {}\n", network.rng.state));
+    text.push_str(&format!("modules {}\n", network.layer_count));
+    for layer in network.layers.iter() {
+        text.push_str(&format!("module {}\n", layer.describe()));
+        let parameters: Vec<String> = layer.parameters().iter().map(|parameter| parameter.to_string()).collect();
+        text.push_str(&format!("parameters {}\n", parameters.join(" ")));
+    }
+    text
+}
+
+pub fn read_network(lines: &mut Lines) -> io::Result<Network> {
+//Reads a network written by network_to_text, from the line with the learning rate to the parameters of the last module.
+    let mut network = Network::empty(parse(&lines.value("learning_rate")?)?);
+    network.cost = match lines.value("cost")?.as_str() {
+        "squared_error" => Cost::SquaredError,
+        "cross_entropy" => Cost::CrossEntropy,
+        other => return Err(invalid(format!("Unknown cost {}", other))),
+    };
+    let rng_state = parse(&lines.value("rng")?)?;
+    if rng_state == 0 {
+        return Err(invalid("The state of the random number generator can't be 0".to_string()))
+    }
+    network.rng = SimpleRng::from_state(rng_state);
+    let module_count: usize = parse(&lines.value("modules")?)?;
+    for _ in 0..module_count {
+        let mut module = module_from_description(&lines.value("module")?)?;
+        let parameter_text = lines.value("parameters")?;
+        let mut parameters = Vec::new();
+        for word in parameter_text.split_whitespace() {
+            parameters.push(parse(word)?);
+        }
+        if parameters.len() != module.parameters().len() {
+            return Err(invalid(format!("The module {} needs {} parameters, but {} were saved", module.describe(), module.parameters().len(), parameters.len())))
+        }
+        module.set_parameters(&parameters);
+        network.push(module);
+    }
+    Ok(network)
+}
+
+pub fn module_from_description(description: &str) -> io::Result<Box<dyn Module>> {
+//Rebuilds a module from the line returned by its describe function. The parameters are random until they are set.
+    let words: Vec<&str> = description.split_whitespace().collect();
+    if words.is_empty() {
+        return Err(invalid("Empty module description".to_string()))
+    }
+    let numbers = |count: usize| -> io::Result<Vec<usize>> {
+        if words.len() != count + 1 {
+            return Err(invalid(format!("Expected {} sizes in the module description {:?}", count, description)))
+        }
+        let mut numbers = Vec::new();
+        for word in words[1..].iter() {
+            numbers.push(parse(word)?);
+        }
+        Ok(numbers)
+    };
+    let module: Box<dyn Module> = match words[0] {
+        "layer" => {
+            if words.len() != 4 {
+                return Err(invalid(format!("Expected 3 values in the module description {:?}", description)))
+            }
+            let inputs: usize = parse(words[1])?;
+            let nodes = parse(words[2])?;
+            check_parameter_count(&[nodes, inputs.saturating_add(1)], description)?;
+            Box::new(Layer::with_activation(inputs, nodes, activation(words[3])?))
+        },
+        "activation" if words.len() == 2 => Box::new(ActivationLayer::new(activation(words[1])?)),
+        "dropout" if words.len() == 2 => {
+            let rate: f32 = parse(words[1])?;
+            if !(0.0..1.0).contains(&rate) {
+                return Err(invalid(format!("The dropout rate ({}) must be in [0; 1[", rate)))
+            }
+            Box::new(Dropout::new(rate))
+        },
+        "normalization" if words.len() == 3 => {
+            let size = parse(words[1])?;
+            check_parameter_count(&[2, size], description)?;
+            let epsilon: f32 = parse(words[2])?;
+            if !(epsilon > 0.0 && epsilon.is_finite()) {
+                return Err(invalid(format!("The epsilon of a normalization ({}) must be a positive number", epsilon)))
+            }
+            let mut normalization = Normalization::new(size);
+            normalization.epsilon = epsilon;
+            Box::new(normalization)
+        },
+        "reshape" if words.len() == 3 => {
+            let input_shape = shape(words[1])?;
+            let output_shape = shape(words[2])?;
+            if shape_size(&input_shape)? != shape_size(&output_shape)? {
+                return Err(invalid(format!("Can't reshape {:?} into {:?}", input_shape, output_shape)))
+            }
+            Box::new(Reshape::new(input_shape, output_shape))
+        },
+        "softmax" if words.len() == 1 => Box::new(Softmax::new()),
+        "conv2d" => {
+            let n = numbers(10)?;
+            check_window(n[2], n[4], n[6], n[8])?;
+            check_window(n[3], n[5], n[7], n[9])?;
+            check_parameter_count(&[n[0], n[1], n[4], n[5]], description)?;
+            Box::new(Conv2d::with_sizes(n[0], n[1], (n[2], n[3]), (n[4], n[5]), (n[6], n[7]), (n[8], n[9])))
+        },
+        "conv1d" => {
+            let n = numbers(6)?;
+            check_window(n[2], n[3], n[4], n[5])?;
+            check_parameter_count(&[n[0], n[1], n[3]], description)?;
+            Box::new(Conv1d::new(n[0], n[1], n[2], n[3], n[4], n[5]))
+        },
+        "maxpool2d" => {
+            let n = numbers(6)?;
+            check_pool(n[1], n[2], n[3], n[4], n[5])?;
+            Box::new(MaxPool2d::new(n[0], (n[1], n[2]), n[3], n[4], n[5]))
+        },
+        "avgpool2d" => {
+            let n = numbers(6)?;
+            check_pool(n[1], n[2], n[3], n[4], n[5])?;
+            Box::new(AvgPool2d::new(n[0], (n[1], n[2]), n[3], n[4], n[5]))
+        },
+        "flatten" if words.len() == 2 => {
+            let input_shape = shape(words[1])?;
+            shape_size(&input_shape)?;
+            Box::new(Flatten::new(input_shape))
+        },
+        _ => return Err(invalid(format!("Unknown module description {:?}", description))),
+    };
+    Ok(module)
+}
+
+fn activation(name: &str) -> io::Result<Activation> {
+    match Activation::from_name(name) {
+        Some(activation) => Ok(activation),
+        None => Err(invalid(format!("Unknown activation {}", name))),
+    }
+}
+
+fn shape(text: &str) -> io::Result<Vec<usize>> {
+    let mut shape = Vec::new();
+    for part in text.split(',') {
+        shape.push(parse(part)?);
+    }
+    Ok(shape)
+}
+
+fn shape_size(shape: &[usize]) -> io::Result<usize> {
+//The number of values in a shape, or an error if there are too many to count.
+    let mut size: usize = 1;
+    for length in shape.iter() {
+        size = match size.checked_mul(*length) {
+            Some(size) => size,
+            None => return Err(invalid(format!("The shape {:?} has too many values", shape))),
+        };
+    }
+    Ok(size)
+}
+
+fn check_parameter_count(factors: &[usize], description: &str) -> io::Result<()> {
+//Checks that the sizes of a module multiply to a number of parameters that can be allocated, and that it has any values at all.
+    let count = shape_size(factors)?;
+    if count == 0 || count > MAX_MODULE_PARAMETERS {
+        return Err(invalid(format!("The module {:?} would have {} parameters", description, count)))
+    }
+    Ok(())
+}
+
+fn check_window(input_length: usize, kernel_length: usize, stride: usize, padding: usize) -> io::Result<()> {
+//Checks the sizes of a convolution along one direction, which would make the module panic if they were wrong.
+    if stride == 0 {
+        return Err(invalid("The stride can't be 0".to_string()))
+    }
+    match padding.checked_mul(2).and_then(|padding| padding.checked_add(input_length)) {
+        Some(padded) if padded >= kernel_length => Ok(()),
+        _ => Err(invalid(format!("The kernel ({}) doesn't fit in the input ({}) with the padding ({})", kernel_length, input_length, padding))),
+    }
+}
+
+fn check_pool(height: usize, width: usize, pool_size: usize, stride: usize, padding: usize) -> io::Result<()> {
+    if padding > pool_size / 2 {
+        return Err(invalid(format!("The padding ({}) can't be more than half the pool size ({})", padding, pool_size)))
+    }
+    check_window(height, pool_size, stride, padding)?;
+    check_window(width, pool_size, stride, padding)
+}
+
+pub(crate) fn parse<T: std::str::FromStr>(text: &str) -> io::Result<T> {
+    match text.trim().parse() {
+        Ok(value) => Ok(value),
+        Err(_) => Err(invalid(format!("Couldn't read the number {:?}", text))),
+    }
+}
+
+pub(crate) fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub struct Lines<'a> {
+//Reads a saved file line by line, skipping empty lines. Also used by other modules that add their own sections to a saved file.
+    lines: std::str::Lines<'a>,
+}
+
+impl<'a> Lines<'a> {
+    pub fn new(text: &'a str) -> Lines<'a> {
+        Lines {
+            lines: text.lines(),
+        }
+    }
+
+    pub fn next_line(&mut self) -> io::Result<String> {
+        loop {
+            match self.lines.next() {
+                Some(line) if line.trim().is_empty() => continue,
+                Some(line) => return Ok(line.trim().to_string()),
+                None => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The file ended too early")),
+            }
+        }
+    }
+
+    pub fn value(&mut self, key: &str) -> io::Result<String> {
+    //Reads the next line, which must start with the key, and returns the rest of it.
+        let line = self.next_line()?;
+        if line == key {
+            return Ok(String::new())
+        }
+        if line.starts_with(key) && line[key.len()..].starts_with(' ') {
+            Ok(line[key.len() + 1..].to_string())
+        } else {
+            Err(invalid(format!("Expected {:?}, but found {:?}", key, line)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset;
+
+    fn train(network: &mut Network, data: &dataset::Dataset, relevant_data: &mut usize, steps: usize) {
+    //Trains on one sample per step, going through the data in order like the app does.
+        for _ in 0..steps {
+            network.find_make_adjust(&data.inputs[*relevant_data], &data.outputs[*relevant_data]);
+            *relevant_data = (*relevant_data + 1) % data.len();
+        }
+    }
+
+    fn new_network(data: &dataset::Dataset) -> Network {
+        let mut rng = SimpleRng::new(11);
+        let mut network = Network::empty(0.5);
+        network.push(Box::new(Layer::from_rng(data.inputs[0].len(), 8, Activation::Tanh, &mut rng)));
+        network.push(Box::new(Dropout::new(0.25))); //Uses the network's rng, so it has to be saved as well.
+        network.push(Box::new(Layer::from_rng(8, data.outputs[0].len(), Activation::Sigmoid, &mut rng)));
+        network.rng = rng;
+        network
+    }
+
+    #[test]
+    fn resumed_training_is_the_same_as_straight_training() {
+        let steps = 200;
+        let data = dataset::generate("moons", 30, &mut SimpleRng::new(5)).unwrap();
+        let mut straight = new_network(&data);
+        let mut relevant_data = 0;
+        train(&mut straight, &data, &mut relevant_data, steps);
+
+        let mut first_half = new_network(&data);
+        let mut relevant_data = 0;
+        train(&mut first_half, &data, &mut relevant_data, steps / 2);
+        let path = std::env::temp_dir().join(format!("simple_nn_resume_test_{}.txt", std::process::id()));
+        let checkpoint = Checkpoint {
+            network: first_half,
+            time: steps / 2,
+            relevant_data,
+            dataset: "moons".to_string(),
+            data_seed: 5,
+            samples: 30,
+        };
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path);
+        fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!((loaded.time, loaded.relevant_data), (steps / 2, relevant_data));
+        let loaded_data = dataset::generate(&loaded.dataset, loaded.samples, &mut SimpleRng::new(loaded.data_seed)).unwrap();
+        assert_eq!(loaded_data.inputs, data.inputs);
+        let mut resumed = loaded.network;
+        let mut relevant_data = loaded.relevant_data;
+        train(&mut resumed, &loaded_data, &mut relevant_data, steps - steps / 2);
+
+        assert_eq!(resumed.rng.state, straight.rng.state);
+        for num in 0..straight.layer_count {
+            assert_eq!(resumed.layers[num].parameters(), straight.layers[num].parameters());
+        }
+    }
+
+    #[test]
+    fn invalid_modules_are_errors() {
+        let descriptions = ["dropout 1", "dropout -0.5", "dropout NaN", "reshape 2,3 5", "conv2d 1 1 4 4 5 5 1 1 0 0", "conv1d 1 1 8 3 0 0",
+            "maxpool2d 1 4 4 2 2 2", "flatten 18446744073709551615,2", "layer 4294967296 4294967296 sigmoid", "layer 18446744073709551615 1 sigmoid",
+            "layer 3 0 sigmoid", "normalization 9223372036854775807 0.00001", "normalization 0 0.00001", "normalization 4 0", "normalization 4 NaN",
+            "conv2d 100000 100000 4 4 3 3 1 1 0 0"];
+        for description in descriptions.iter() {
+            assert!(module_from_description(description).is_err(), "{}", description);
+        }
+    }
+
+    #[test]
+    fn rng_state_of_zero_is_an_error() {
+        let text = network_to_text(&Network::with_seed(vec![2, 2], 0.5, 1));
+        let rng_line = format!("rng {}", Network::with_seed(vec![2, 2], 0.5, 1).rng.state);
+        let text = text.replace(&rng_line, "rng 0");
+        assert!(read_network(&mut Lines::new(&text)).is_err());
+    }
+}
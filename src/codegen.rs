@@ -0,0 +1,436 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::nodes_layers::{Activation, Network};
+
+//Writes a network as source code that needs nothing else, so a trained network can run on e.g. a microcontroller.
+//The code holds the weights and biases as constant arrays and has a predict function that calculates the outputs like Network::calculate.
+//
+//The Rust code only uses core, so it can be a module of a #![no_std] crate. It has its own copies of the expf and tanhf of glibc and musl,
+//so it gives the same values as Network::calculate to the last bit, except that glibc's expf on CPUs with FMA differs by one bit for 2 of the 2^32 inputs.
+//The C code uses expf and tanhf from math.h, and should be compiled with -ffp-contract=off so the sums are rounded the same way as in Rust.
+//
+//Dense layers, activation layers and softmax are supported. Dropout, Flatten and Reshape are left out, since they don't change the values outside training.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Language {
+    Rust,
+    C,
+}
+
+enum Step {
+    Dense {weights: Vec<Vec<f32>>, biases: Vec<f32>, activation: Activation},
+    Activation(Activation),
+    Softmax,
+}
+
+pub fn save_code(network: &Network, language: Language, path: &Path) -> io::Result<()> {
+    fs::write(path, generate_code(network, language)?)
+}
+
+pub fn generate_code(network: &Network, language: Language) -> io::Result<String> {
+//The source code of a predict function for the network. Modules that can't be written as code give an error.
+    let (steps, input_size) = steps(network)?;
+    let mut sizes = vec![input_size];
+    for step in steps.iter() {
+        let size = match step {
+            Step::Dense {biases, ..} => biases.len(),
+            _ => sizes[sizes.len() - 1],
+        };
+        sizes.push(size);
+    }
+    let activations: Vec<Activation> = steps.iter().filter_map(|step| match step {
+        Step::Dense {activation, ..} => Some(*activation),
+        Step::Activation(activation) => Some(*activation),
+        Step::Softmax => None,
+    }).collect();
+    let uses_sigmoid = activations.contains(&Activation::Sigmoid);
+    let uses_tanh = activations.contains(&Activation::Tanh);
+    let uses_softmax = steps.iter().any(|step| matches!(step, Step::Softmax));
+    let layer_sizes: Vec<String> = steps.iter().zip(sizes[1..].iter()).filter_map(|(step, size)| match step {
+        Step::Dense {..} => Some(size.to_string()),
+        _ => None,
+    }).collect();
+    let description = format!("a network with {} inputs and dense layers of {} nodes", input_size, layer_sizes.join(", "));
+    match language {
+        Language::Rust => Ok(rust_code(&steps, &sizes, &description, uses_sigmoid || uses_softmax, uses_sigmoid, uses_tanh)),
+        Language::C => Ok(c_code(&steps, &sizes, &description, uses_sigmoid)),
+    }
+}
+
+fn steps(network: &Network) -> io::Result<(Vec<Step>, usize)> {
+//The calculations of the network, and its number of inputs, which is found from the first dense layer.
+    let mut steps = Vec::new();
+    let mut input_size = None;
+    for module in network.layers.iter() {
+        if let Some(layer) = module.as_layer() {
+            let mut weights = Vec::new();
+            for node in layer.nodes.iter() {
+                check_finite(&node.weights)?;
+                weights.push(node.weights.clone());
+            }
+            let biases: Vec<f32> = layer.nodes.iter().map(|node| node.bias).collect();
+            check_finite(&biases)?;
+            if input_size.is_none() {
+                input_size = Some(if layer.node_count > 0 {layer.nodes[0].weights.len()} else {0});
+            }
+            steps.push(Step::Dense {weights, biases, activation: layer.activation});
+        } else if let Some(activation_layer) = module.as_activation_layer() {
+            steps.push(Step::Activation(activation_layer.activation));
+        } else if module.as_softmax().is_some() {
+            steps.push(Step::Softmax);
+        } else if !module.passes_values_on() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("The module {} can't be written as code", module.describe())))
+        }
+    }
+    match input_size {
+        Some(input_size) => Ok((steps, input_size)),
+        None => Err(io::Error::new(io::ErrorKind::InvalidInput, "The network needs at least one dense layer to know its number of inputs")),
+    }
+}
+
+fn check_finite(values: &[f32]) -> io::Result<()> {
+    if values.iter().any(|value| !value.is_finite()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The network has weights that aren't finite numbers, so it has probably diverged"))
+    }
+    Ok(())
+}
+
+fn number(value: f32, language: Language) -> String {
+//The shortest number that is read back as exactly the same f32.
+    let text = format!("{:?}", value);
+    match language {
+        Language::Rust => text,
+        Language::C => format!("{}f", text),
+    }
+}
+
+fn numbers(values: &[f32], language: Language) -> String {
+    values.iter().map(|value| number(*value, language)).collect::<Vec<String>>().join(", ")
+}
+
+fn rust_code(steps: &[Step], sizes: &[usize], description: &str, uses_exp: bool, uses_sigmoid: bool, uses_tanh: bool) -> String {
+    let mut code = format!("// Generated by simple_nn from {}.\n", description);
+    code.push_str("// Only uses core, so it can be a module of a #![no_std] crate.\n\n");
+    code.push_str(&format!("pub const INPUTS: usize = {};\npub const OUTPUTS: usize = {};\n", sizes[0], sizes[sizes.len() - 1]));
+    for (step_num, step) in steps.iter().enumerate() {
+        if let Step::Dense {weights, biases, ..} = step {
+            code.push_str(&format!("\nconst WEIGHTS_{}: [[f32; {}]; {}] = [\n", step_num, sizes[step_num], biases.len()));
+            for node_weights in weights.iter() {
+                code.push_str(&format!("    [{}],\n", numbers(node_weights, Language::Rust)));
+            }
+            code.push_str(&format!("];\nconst BIASES_{}: [f32; {}] = [{}];\n", step_num, biases.len(), numbers(biases, Language::Rust)));
+        }
+    }
+
+    code.push_str("\npub fn predict(inputs: &[f32; INPUTS]) -> [f32; OUTPUTS] {\n");
+    let mut previous = "*inputs".to_string();
+    for (step_num, step) in steps.iter().enumerate() {
+        let size = sizes[step_num + 1];
+        let name = format!("values_{}", step_num);
+        match step {
+            Step::Dense {activation, ..} => {
+                code.push_str(&format!("    let mut {} = [0.0f32; {}];\n", name, size));
+                code.push_str(&format!("    for node in 0..{} {{\n", size));
+                code.push_str(&format!("        let mut sum = BIASES_{}[node];\n", step_num));
+                code.push_str(&format!("        for num in 0..{} {{\n", sizes[step_num]));
+                code.push_str(&format!("            sum += {}[num] * WEIGHTS_{}[node][num];\n", previous.trim_start_matches('*'), step_num));
+                code.push_str("        }\n");
+                code.push_str(&format!("        {}[node] = {};\n", name, rust_activation(activation, "sum")));
+                code.push_str("    }\n");
+            },
+            Step::Activation(activation) => {
+                code.push_str(&format!("    let mut {} = {};\n", name, previous));
+                code.push_str(&format!("    for num in 0..{} {{\n", size));
+                code.push_str(&format!("        {}[num] = {};\n", name, rust_activation(activation, &format!("{}[num]", name))));
+                code.push_str("    }\n");
+            },
+            Step::Softmax => { // Subtracting the largest value keeps exp from overflowing.
+                code.push_str(&format!("    let mut {} = {};\n", name, previous));
+                code.push_str("    let mut max = f32::NEG_INFINITY;\n");
+                code.push_str(&format!("    for num in 0..{} {{\n", size));
+                code.push_str(&format!("        max = max.max({}[num]);\n", name));
+                code.push_str("    }\n");
+                code.push_str("    let mut sum = 0.0;\n");
+                code.push_str(&format!("    for num in 0..{} {{\n", size));
+                code.push_str(&format!("        {}[num] = expf({}[num] - max);\n", name, name));
+                code.push_str(&format!("        sum += {}[num];\n", name));
+                code.push_str("    }\n");
+                code.push_str(&format!("    for num in 0..{} {{\n", size));
+                code.push_str(&format!("        {}[num] /= sum;\n", name));
+                code.push_str("    }\n");
+            },
+        }
+        previous = name;
+    }
+    code.push_str(&format!("    {}\n}}\n", previous));
+
+    if uses_sigmoid {
+        code.push_str("\nfn sigmoid(value: f32) -> f32 {\n    1.0 / (1.0 + expf(-value))\n}\n");
+    }
+    if uses_exp {
+        code.push('\n');
+        code.push_str(RUST_EXPF);
+    }
+    if uses_tanh {
+        code.push('\n');
+        code.push_str(RUST_TANHF);
+    }
+    code
+}
+
+fn rust_activation(activation: &Activation, value: &str) -> String {
+    match activation {
+        Activation::Sigmoid => format!("sigmoid({})", value),
+        Activation::Tanh => format!("tanhf({})", value),
+        Activation::Relu => format!("if {} > 0.0 {{{}}} else {{0.0}}", value, value),
+        Activation::Identity => value.to_string(),
+    }
+}
+
+fn c_code(steps: &[Step], sizes: &[usize], description: &str, uses_sigmoid: bool) -> String {
+    let mut code = format!("/* Generated by simple_nn from {}.\n", description);
+    code.push_str("   Compile with -ffp-contract=off to get the same values as Network::calculate. */\n\n");
+    code.push_str("#include <math.h>\n\n");
+    code.push_str(&format!("#define NETWORK_INPUTS {}\n#define NETWORK_OUTPUTS {}\n", sizes[0], sizes[sizes.len() - 1]));
+    for (step_num, step) in steps.iter().enumerate() {
+        if let Step::Dense {weights, biases, ..} = step {
+            code.push_str(&format!("\nstatic const float weights_{}[{}][{}] = {{\n", step_num, biases.len(), sizes[step_num]));
+            for node_weights in weights.iter() {
+                code.push_str(&format!("    {{{}}},\n", numbers(node_weights, Language::C)));
+            }
+            code.push_str(&format!("}};\nstatic const float biases_{}[{}] = {{{}}};\n", step_num, biases.len(), numbers(biases, Language::C)));
+        }
+    }
+    if uses_sigmoid {
+        code.push_str("\nstatic float sigmoid(float value) {\n    return 1.0f / (1.0f + expf(-value));\n}\n");
+    }
+
+    code.push_str("\nvoid predict(const float inputs[NETWORK_INPUTS], float outputs[NETWORK_OUTPUTS]) {\n");
+    let mut previous = "inputs".to_string();
+    for (step_num, step) in steps.iter().enumerate() {
+        let size = sizes[step_num + 1];
+        let name = format!("values_{}", step_num);
+        code.push_str(&format!("    float {}[{}];\n", name, size));
+        match step {
+            Step::Dense {activation, ..} => {
+                code.push_str(&format!("    for (int node = 0; node < {}; node++) {{\n", size));
+                code.push_str(&format!("        float sum = biases_{}[node];\n", step_num));
+                code.push_str(&format!("        for (int num = 0; num < {}; num++) {{\n", sizes[step_num]));
+                code.push_str(&format!("            sum += {}[num] * weights_{}[node][num];\n", previous, step_num));
+                code.push_str("        }\n");
+                code.push_str(&format!("        {}[node] = {};\n", name, c_activation(activation, "sum")));
+                code.push_str("    }\n");
+            },
+            Step::Activation(activation) => {
+                code.push_str(&format!("    for (int num = 0; num < {}; num++) {{\n", size));
+                code.push_str(&format!("        {}[num] = {};\n", name, c_activation(activation, &format!("{}[num]", previous))));
+                code.push_str("    }\n");
+            },
+            Step::Softmax => {
+                code.push_str(&format!("    float max_{} = -INFINITY;\n", step_num));
+                code.push_str(&format!("    for (int num = 0; num < {}; num++) {{\n", size));
+                code.push_str(&format!("        max_{} = fmaxf(max_{}, {}[num]);\n", step_num, step_num, previous));
+                code.push_str("    }\n");
+                code.push_str(&format!("    float sum_{} = 0.0f;\n", step_num));
+                code.push_str(&format!("    for (int num = 0; num < {}; num++) {{\n", size));
+                code.push_str(&format!("        {}[num] = expf({}[num] - max_{});\n", name, previous, step_num));
+                code.push_str(&format!("        sum_{} += {}[num];\n", step_num, name));
+                code.push_str("    }\n");
+                code.push_str(&format!("    for (int num = 0; num < {}; num++) {{\n", size));
+                code.push_str(&format!("        {}[num] /= sum_{};\n", name, step_num));
+                code.push_str("    }\n");
+            },
+        }
+        previous = name;
+    }
+    code.push_str("    for (int num = 0; num < NETWORK_OUTPUTS; num++) {\n");
+    code.push_str(&format!("        outputs[num] = {}[num];\n", previous));
+    code.push_str("    }\n}\n");
+    code
+}
+
+fn c_activation(activation: &Activation, value: &str) -> String {
+    match activation {
+        Activation::Sigmoid => format!("sigmoid({})", value),
+        Activation::Tanh => format!("tanhf({})", value),
+        Activation::Relu => format!("{} > 0.0f ? {} : 0.0f", value, value),
+        Activation::Identity => value.to_string(),
+    }
+}
+
+//The expf and tanhf the generated Rust code uses, since f32::exp and f32::tanh need std.
+//They are kept in files of their own so the tests can compile them and compare them with std.
+const RUST_EXPF: &str = include_str!("codegen/expf.rs");
+
+const RUST_TANHF: &str = include_str!("codegen/tanhf.rs");
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+    use crate::conv::Conv2d;
+    use crate::modules::{ActivationLayer, Softmax};
+    use crate::nodes_layers::Layer;
+    use crate::rng::SimpleRng;
+
+    include!("codegen/expf.rs");
+    include!("codegen/tanhf.rs");
+
+    fn assert_same(result: f32, expected: f32, x: f32) {
+        assert!(result.to_bits() == expected.to_bits() || (result.is_nan() && expected.is_nan()), "{:e} gives {:e} instead of {:e}", x, result, expected);
+    }
+
+    #[test]
+    fn expf_and_tanhf_match_std() {
+        //Every 9973rd bit pattern, which goes through every range of both functions, including the infinities and NaN.
+        let mut bits = 0u32;
+        loop {
+            let x = f32::from_bits(bits);
+            assert_same(expf(x), x.exp(), x);
+            assert_same(tanhf(x), x.tanh(), x);
+            bits = match bits.checked_add(9973) {
+                Some(bits) => bits,
+                None => break,
+            };
+        }
+        let edges = [0.0, -0.0, 1.0, -1.0, 0.5, 22.0, -22.0, 88.72283, 88.72284, -87.33654, -103.97208, -103.97209, f32::INFINITY, f32::NEG_INFINITY, f32::MIN_POSITIVE];
+        for x in edges.iter() {
+            assert_same(expf(*x), x.exp(), *x);
+            assert_same(tanhf(*x), x.tanh(), *x);
+        }
+    }
+
+    fn mixed_network() -> Network {
+    //A dense layer of each activation function, with a softmax at the end.
+        let mut rng = SimpleRng::new(4);
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer::from_rng(3, 5, Activation::Sigmoid, &mut rng)));
+        network.push(Box::new(Layer::from_rng(5, 4, Activation::Tanh, &mut rng)));
+        network.push(Box::new(ActivationLayer::new(Activation::Relu)));
+        network.push(Box::new(Layer::from_rng(4, 3, Activation::Identity, &mut rng)));
+        network.push(Box::new(Softmax::new()));
+        network
+    }
+
+    const SAMPLES: [[f32; 3]; 4] = [[0.0, 0.0, 0.0], [1.0, -0.5, 0.25], [-2.0, 3.0, 0.7], [10.0, -10.0, 1e-3]];
+
+    fn expected_bits(network: &Network) -> Vec<String> {
+        let mut lines = Vec::new();
+        for inputs in SAMPLES.iter() {
+            let values = network.calculate(inputs);
+            for value in values[values.len() - 1].iter() {
+                lines.push(format!("{:08x}", value.to_bits()));
+            }
+        }
+        lines
+    }
+
+    fn compile_and_run(compiler: &str, arguments: &[&str], source: &str, extension: &str) -> Option<Vec<String>> {
+    //Compiles the source and returns the lines it prints, or None if the compiler isn't installed.
+        let folder = std::env::temp_dir().join(format!("simple_nn_codegen_{}_{}", extension, std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+        let source_path = folder.join(format!("predict.{}", extension));
+        let program_path = folder.join("predict");
+        fs::write(&source_path, source).unwrap();
+        let compiled = Command::new(compiler).arg(&source_path).args(arguments).arg("-o").arg(&program_path).output();
+        let result = match compiled {
+            Err(_) => None,
+            Ok(output) => {
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+                let output = Command::new(&program_path).output().unwrap();
+                Some(String::from_utf8(output.stdout).unwrap().lines().map(|line| line.to_string()).collect())
+            },
+        };
+        fs::remove_dir_all(&folder).unwrap();
+        result
+    }
+
+    #[test]
+    fn rust_code_has_the_weights_and_gives_the_same_outputs() {
+        let network = mixed_network();
+        let code = generate_code(&network, Language::Rust).unwrap();
+        assert!(code.contains("pub const INPUTS: usize = 3;\npub const OUTPUTS: usize = 3;\n"));
+        for name in ["pub fn predict(", "fn sigmoid(", "fn expf(", "fn tanhf(", "const WEIGHTS_0: [[f32; 3]; 5]", "const WEIGHTS_3: [[f32; 4]; 3]"].iter() {
+            assert!(code.contains(name), "{}", name);
+        }
+        for module in network.layers.iter() {
+            if let Some(layer) = module.as_layer() {
+                for node in layer.nodes.iter() {
+                    assert!(code.contains(&format!("[{}]", numbers(&node.weights, Language::Rust))));
+                }
+            }
+        }
+
+        let mut program = code.clone();
+        program.push_str("\nfn main() {\n");
+        for inputs in SAMPLES.iter() {
+            program.push_str(&format!("    for value in predict(&[{}]).iter() {{\n        println!(\"{{:08x}}\", value.to_bits());\n    }}\n", numbers(inputs, Language::Rust)));
+        }
+        program.push_str("}\n");
+        if let Some(lines) = compile_and_run("rustc", &["--edition", "2018", "-O", "-A", "warnings"], &program, "rs") {
+            assert_eq!(lines, expected_bits(&network));
+        }
+    }
+
+    #[test]
+    fn c_code_has_the_weights_and_gives_the_same_outputs() {
+        let network = mixed_network();
+        let code = generate_code(&network, Language::C).unwrap();
+        for name in ["#include <math.h>", "#define NETWORK_INPUTS 3\n#define NETWORK_OUTPUTS 3\n", "void predict(", "static float sigmoid(", "tanhf(", "expf(",
+            "static const float weights_0[5][3]"].iter() {
+            assert!(code.contains(name), "{}", name);
+        }
+        for module in network.layers.iter() {
+            if let Some(layer) = module.as_layer() {
+                for node in layer.nodes.iter() {
+                    assert!(code.contains(&format!("{{{}}}", numbers(&node.weights, Language::C))));
+                }
+            }
+        }
+
+        let mut program = code.clone();
+        program.push_str("\n#include <stdio.h>\n#include <string.h>\n\nint main(void) {\n");
+        program.push_str(&format!("    const float samples[{}][NETWORK_INPUTS] = {{\n", SAMPLES.len()));
+        for inputs in SAMPLES.iter() {
+            program.push_str(&format!("        {{{}}},\n", numbers(inputs, Language::C)));
+        }
+        program.push_str(&format!("    }};\n    for (int sample = 0; sample < {}; sample++) {{\n", SAMPLES.len()));
+        program.push_str("        float outputs[NETWORK_OUTPUTS];\n        predict(samples[sample], outputs);\n");
+        program.push_str("        for (int num = 0; num < NETWORK_OUTPUTS; num++) {\n            unsigned int bits;\n            memcpy(&bits, &outputs[num], 4);\n            printf(\"%08x\\n\", bits);\n        }\n    }\n    return 0;\n}\n");
+        if let Some(lines) = compile_and_run("cc", &["-O2", "-ffp-contract=off", "-lm"], &program, "c") {
+            assert_eq!(lines, expected_bits(&network));
+        }
+    }
+
+    #[test]
+    fn only_the_functions_used_are_written() {
+        let network = Network::with_seed(vec![2, 3, 1], 0.5, 2);
+        let mut relu_network = Network::empty(0.1);
+        relu_network.push(Box::new(Layer::from_rng(2, 2, Activation::Relu, &mut SimpleRng::new(1))));
+        let code = generate_code(&relu_network, Language::Rust).unwrap();
+        assert!(!code.contains("fn expf(") && !code.contains("fn tanhf(") && !code.contains("fn sigmoid("));
+        let code = generate_code(&network, Language::Rust).unwrap();
+        assert!(code.contains("fn sigmoid(") && code.contains("fn expf(") && !code.contains("fn tanhf("));
+    }
+
+    #[test]
+    fn networks_that_cant_be_written_are_errors() {
+        let mut rng = SimpleRng::new(1);
+        let mut conv_network = Network::empty(0.1);
+        conv_network.push(Box::new(Conv2d::from_rng(1, 2, (4, 4), (3, 3), (1, 1), (0, 0), &mut rng)));
+        conv_network.push(Box::new(Layer::from_rng(8, 2, Activation::Sigmoid, &mut rng)));
+        let mut diverged = Network::with_seed(vec![2, 3, 1], 0.5, 2);
+        let mut softmax_only = Network::empty(0.1);
+        softmax_only.push(Box::new(Softmax::new()));
+        for language in [Language::Rust, Language::C].iter() {
+            assert!(generate_code(&conv_network, *language).is_err());
+            assert!(generate_code(&softmax_only, *language).is_err());
+        }
+        diverged.layers[1].set_parameters(&[f32::NAN, 1.0, 1.0, 1.0]);
+        assert!(generate_code(&diverged, Language::Rust).is_err());
+        assert!(generate_code(&diverged, Language::C).is_err());
+    }
+}
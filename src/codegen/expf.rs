@@ -0,0 +1,37 @@
+const EXP2F_TABLE: [u64; 32] = [
+    0x3ff0000000000000, 0x3fefd9b0d3158574, 0x3fefb5586cf9890f, 0x3fef9301d0125b51, 0x3fef72b83c7d517b, 0x3fef54873168b9aa, 0x3fef387a6e756238, 0x3fef1e9df51fdee1,
+    0x3fef06fe0a31b715, 0x3feef1a7373aa9cb, 0x3feedea64c123422, 0x3feece086061892d, 0x3feebfdad5362a27, 0x3feeb42b569d4f82, 0x3feeab07dd485429, 0x3feea47eb03a5585,
+    0x3feea09e667f3bcd, 0x3fee9f75e8ec5f74, 0x3feea11473eb0187, 0x3feea589994cce13, 0x3feeace5422aa0db, 0x3feeb737b0cdc5e5, 0x3feec49182a3f090, 0x3feed503b23e255d,
+    0x3feee89f995ad3ad, 0x3feeff76f2fb5e47, 0x3fef199bdd85529c, 0x3fef3720dcef9069, 0x3fef5818dcfba487, 0x3fef7c97337b9b5f, 0x3fefa4afa2a490da, 0x3fefd0765b6e4540,
+];
+
+fn expf(x: f32) -> f32 {
+    // The expf of glibc and musl, so the results are the same to the bit as f32::exp on those systems.
+    let abstop = (x.to_bits() >> 20) & 0x7ff;
+    if abstop >= 0x42b { // |x| >= 88 or NaN
+        if x.to_bits() == 0xff800000 {
+            return 0.0;
+        }
+        if abstop >= 0x7f8 {
+            return x + x;
+        }
+        if x > f32::from_bits(0x42b17217) {
+            return f32::INFINITY;
+        }
+        if x < f32::from_bits(0xc2cff1b4) {
+            return 0.0;
+        }
+    }
+    let shift = f64::from_bits(0x4338000000000000);
+    let z = f64::from_bits(0x40471547652b82fe) * x as f64;
+    let kd = z + shift;
+    let ki = kd.to_bits();
+    let kd = kd - shift;
+    let r = z - kd;
+    let s = f64::from_bits(EXP2F_TABLE[(ki % 32) as usize].wrapping_add(ki << 47));
+    let z = f64::from_bits(0x3ebc6af84b912394) * r + f64::from_bits(0x3f2ebfce50fac4f3);
+    let r2 = r * r;
+    let y = f64::from_bits(0x3f962e42ff0c52d6) * r + 1.0;
+    let y = z * r2 + y;
+    (y * s) as f32
+}
@@ -0,0 +1,108 @@
+fn expm1f(x: f32) -> f32 {
+    // The expm1f of glibc, which its tanhf uses.
+    let ln2_hi = f32::from_bits(0x3f317180);
+    let ln2_lo = f32::from_bits(0x3717f7d1);
+    let mut x = x;
+    let mut hx = x.to_bits();
+    let negative = hx & 0x80000000 != 0;
+    hx &= 0x7fffffff;
+    if hx >= 0x4195b844 { // |x| >= 27 * ln2
+        if hx >= 0x42b17218 {
+            if hx > 0x7f800000 {
+                return x + x;
+            }
+            if hx == 0x7f800000 {
+                return if negative {-1.0} else {x};
+            }
+            if x > f32::from_bits(0x42b17180) {
+                return f32::INFINITY;
+            }
+        }
+        if negative {
+            return -1.0;
+        }
+    }
+    let k: i32;
+    let mut c = 0.0;
+    if hx > 0x3eb17218 { // |x| > 0.5 * ln2
+        let (hi, lo);
+        if hx < 0x3f851592 { // |x| < 1.5 * ln2
+            if negative {
+                hi = x + ln2_hi;
+                lo = -ln2_lo;
+                k = -1;
+            } else {
+                hi = x - ln2_hi;
+                lo = ln2_lo;
+                k = 1;
+            }
+        } else {
+            k = (f32::from_bits(0x3fb8aa3b) * x + if negative {-0.5} else {0.5}) as i32;
+            let t = k as f32;
+            hi = x - t * ln2_hi;
+            lo = t * ln2_lo;
+        }
+        x = hi - lo;
+        c = (hi - x) - lo;
+    } else if hx < 0x33000000 { // |x| < 2^-25
+        return x;
+    } else {
+        k = 0;
+    }
+    let hfx = 0.5 * x;
+    let hxs = x * hfx;
+    let r1 = 1.0 + hxs * (f32::from_bits(0xbd088889) + hxs * (f32::from_bits(0x3ad00d01) + hxs * (f32::from_bits(0xb8a670cd)
+        + hxs * (f32::from_bits(0x36867e54) + hxs * f32::from_bits(0xb457edbb)))));
+    let t = 3.0 - r1 * hfx;
+    let mut e = hxs * ((r1 - t) / (6.0 - x * t));
+    if k == 0 {
+        return x - (x * e - hxs);
+    }
+    e = x * (e - c) - c;
+    e -= hxs;
+    if k == -1 {
+        return 0.5 * (x - e) - 0.5;
+    }
+    if k == 1 {
+        return if x < -0.25 {-2.0 * (e - (x + 0.5))} else {1.0 + 2.0 * (x - e)};
+    }
+    if k <= -2 || k > 56 {
+        let y = 1.0 - (e - x);
+        return f32::from_bits((y.to_bits() as i32).wrapping_add(k << 23) as u32) - 1.0;
+    }
+    let y = if k < 23 {
+        let t = f32::from_bits((0x3f800000 - (0x1000000 >> k)) as u32);
+        t - (e - x)
+    } else {
+        let t = f32::from_bits(((0x7f - k) << 23) as u32);
+        x - (e + t) + 1.0
+    };
+    f32::from_bits((y.to_bits() as i32).wrapping_add(k << 23) as u32)
+}
+
+fn tanhf(x: f32) -> f32 {
+    // The tanhf of glibc, so the results are the same to the bit as f32::tanh there.
+    let jx = x.to_bits();
+    let ix = jx & 0x7fffffff;
+    if ix >= 0x7f800000 {
+        return if jx & 0x80000000 == 0 {1.0 / x + 1.0} else {1.0 / x - 1.0};
+    }
+    let z = if ix < 0x41b00000 { // |x| < 22
+        if ix == 0 {
+            return x;
+        }
+        if ix < 0x24000000 { // |x| < 2^-55
+            return x * (1.0 + x);
+        }
+        if ix >= 0x3f800000 { // |x| >= 1
+            let t = expm1f(2.0 * f32::from_bits(ix));
+            1.0 - 2.0 / (t + 2.0)
+        } else {
+            let t = expm1f(-2.0 * f32::from_bits(ix));
+            -t / (t + 2.0)
+        }
+    } else {
+        1.0
+    };
+    if jx & 0x80000000 == 0 {z} else {-z}
+}
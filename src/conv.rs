@@ -0,0 +1,639 @@
+use rand::Rng;
+
+use crate::modules::shape_text;
+use crate::nodes_layers::Module;
+use crate::rng::SimpleRng;
+
+//All modules in this file read their values as channels of rows of positions, stored in one list.
+//The value at channel c, row y and column x of an image with height h and width w is values[(c * h + y) * w + x].
+
+fn output_length(input_length: usize, kernel_length: usize, stride: usize, padding: usize) -> usize {
+//The number of positions a kernel can be placed at along one direction.
+    if input_length + 2 * padding < kernel_length {
+        panic!("The kernel ({}) is larger than the padded input ({})", kernel_length, input_length + 2 * padding);
+    }
+    (input_length + 2 * padding - kernel_length) / stride + 1
+}
+
+fn input_position(output_pos: usize, kernel_pos: usize, stride: usize, padding: usize, input_length: usize) -> Option<usize> {
+//Finds the input position a kernel position lands on, or None if it lands in the padding.
+    let position = (output_pos * stride + kernel_pos) as isize - padding as isize;
+    if position < 0 || position >= input_length as isize {
+        None
+    } else {
+        Some(position as usize)
+    }
+}
+
+#[derive(Clone)]
+pub struct Conv2d {
+//A 2D convolution. Every output channel has a bias and a kernel of weights for each input channel,
+//which is slid over the input with the given stride. The input is padded with zeros.
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub input_size: (usize, usize), //(height, width)
+    pub kernel_size: (usize, usize),
+    pub stride: (usize, usize),
+    pub padding: (usize, usize),
+    pub biases: Vec<f32>,
+    pub weights: Vec<f32>, //weights[((out_channel * in_channels + in_channel) * kernel height + ky) * kernel width + kx]
+    bias_adjusts: Vec<f32>,
+    weight_adjusts: Vec<f32>,
+}
+
+impl Conv2d {
+    pub fn new(in_channels: usize, out_channels: usize, input_size: (usize, usize), kernel_size: usize, stride: usize, padding: usize) -> Conv2d {
+    //Generates a convolution with square kernels, random biases and random weights.
+        Conv2d::with_sizes(in_channels, out_channels, input_size, (kernel_size, kernel_size), (stride, stride), (padding, padding))
+    }
+
+    pub fn with_sizes(in_channels: usize, out_channels: usize, input_size: (usize, usize), kernel_size: (usize, usize), stride: (usize, usize), padding: (usize, usize)) -> Conv2d {
+    //Like new, but with different sizes along the height and the width.
+        let mut rng = rand::thread_rng();
+        let weight_count = out_channels * in_channels * kernel_size.0 * kernel_size.1;
+        let mut weights = Vec::new();
+        for _ in 0..weight_count {
+            let x: f32 = rng.gen();  // Random number in the interval [0; 1[
+            weights.push(2.0 * x - 1.0);  // The initial weights will be in [-1; 1[
+        }
+        let mut biases = Vec::new();
+        for _ in 0..out_channels {
+            let x: f32 = rng.gen();
+            biases.push(2.0 * x - 1.0);
+        }
+        Conv2d::with_parameters(in_channels, out_channels, input_size, kernel_size, stride, padding, biases, weights)
+    }
+
+    pub fn from_rng(in_channels: usize, out_channels: usize, input_size: (usize, usize), kernel_size: (usize, usize), stride: (usize, usize), padding: (usize, usize), rng: &mut SimpleRng) -> Conv2d {
+    //Like with_sizes, but the random biases and weights come from rng, so a network of convolutions can be made from a seed.
+        let weight_count = out_channels * in_channels * kernel_size.0 * kernel_size.1;
+        let mut weights = Vec::new();
+        for _ in 0..weight_count {
+            weights.push(rng.gen_range(-1.0, 1.0));
+        }
+        let mut biases = Vec::new();
+        for _ in 0..out_channels {
+            biases.push(rng.gen_range(-1.0, 1.0));
+        }
+        Conv2d::with_parameters(in_channels, out_channels, input_size, kernel_size, stride, padding, biases, weights)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn with_parameters(in_channels: usize, out_channels: usize, input_size: (usize, usize), kernel_size: (usize, usize), stride: (usize, usize), padding: (usize, usize), biases: Vec<f32>, weights: Vec<f32>) -> Conv2d {
+        if stride.0 == 0 || stride.1 == 0 {
+            panic!("The stride can't be 0");
+        }
+        let weight_count = weights.len();
+        let conv = Conv2d {
+            in_channels,
+            out_channels,
+            input_size,
+            kernel_size,
+            stride,
+            padding,
+            biases,
+            weights,
+            bias_adjusts: vec![0.0; out_channels],
+            weight_adjusts: vec![0.0; weight_count],
+        };
+        conv.output_size(); //Panics early if the kernel doesn't fit.
+        conv
+    }
+
+    pub fn output_size(&self) -> (usize, usize) {
+    //The (height, width) of each output channel.
+        (output_length(self.input_size.0, self.kernel_size.0, self.stride.0, self.padding.0),
+         output_length(self.input_size.1, self.kernel_size.1, self.stride.1, self.padding.1))
+    }
+
+    fn check_inputs(&self, inputs: &[f32]) {
+        let input_len = self.in_channels * self.input_size.0 * self.input_size.1;
+        if inputs.len() != input_len {
+            panic!("The convolution expects {} values ({} channels of {:?}), but received {}", input_len, self.in_channels, self.input_size, inputs.len());
+        }
+    }
+}
+
+impl Module for Conv2d {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let (output_height, output_width) = self.output_size();
+        let mut values = vec![0.0; self.out_channels * output_height * output_width];
+        self.forward_into(inputs, &mut values);
+        values
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        self.check_inputs(inputs);
+        let (height, width) = self.input_size;
+        let (kernel_height, kernel_width) = self.kernel_size;
+        let (output_height, output_width) = self.output_size();
+        let mut output_num = 0;
+        for out_channel in 0..self.out_channels {
+            for out_y in 0..output_height {
+                for out_x in 0..output_width {
+                    let mut value = self.biases[out_channel];
+                    for in_channel in 0..self.in_channels {
+                        for ky in 0..kernel_height {
+                            let y = match input_position(out_y, ky, self.stride.0, self.padding.0, height) {Some(y) => y, None => continue};
+                            for kx in 0..kernel_width {
+                                let x = match input_position(out_x, kx, self.stride.1, self.padding.1, width) {Some(x) => x, None => continue};
+                                let weight_num = ((out_channel * self.in_channels + in_channel) * kernel_height + ky) * kernel_width + kx;
+                                value += inputs[(in_channel * height + y) * width + x] * self.weights[weight_num];
+                            }
+                        }
+                    }
+                    outputs[output_num] = value;
+                    output_num += 1;
+                }
+            }
+        }
+    }
+
+    fn output_count(&self, _input_count: usize) -> usize {
+        let (output_height, output_width) = self.output_size();
+        self.out_channels * output_height * output_width
+    }
+
+    fn backward(&mut self, inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+    //Every weight is used at many positions, so its adjust is the sum over all the positions.
+        self.check_inputs(inputs);
+        let (height, width) = self.input_size;
+        let (kernel_height, kernel_width) = self.kernel_size;
+        let (output_height, output_width) = self.output_size();
+        let mut input_deltas = vec![0.0; inputs.len()];
+        for out_channel in 0..self.out_channels {
+            for out_y in 0..output_height {
+                for out_x in 0..output_width {
+                    let delta = output_deltas[(out_channel * output_height + out_y) * output_width + out_x];
+                    self.bias_adjusts[out_channel] += delta;
+                    for in_channel in 0..self.in_channels {
+                        for ky in 0..kernel_height {
+                            let y = match input_position(out_y, ky, self.stride.0, self.padding.0, height) {Some(y) => y, None => continue};
+                            for kx in 0..kernel_width {
+                                let x = match input_position(out_x, kx, self.stride.1, self.padding.1, width) {Some(x) => x, None => continue};
+                                let weight_num = ((out_channel * self.in_channels + in_channel) * kernel_height + ky) * kernel_width + kx;
+                                let input_num = (in_channel * height + y) * width + x;
+                                self.weight_adjusts[weight_num] += delta * inputs[input_num];
+                                input_deltas[input_num] += delta * self.weights[weight_num];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        input_deltas
+    }
+
+    fn parameters(&self) -> Vec<f32> {
+    //The biases followed by the weights.
+        let mut parameters = self.biases.clone();
+        parameters.extend_from_slice(&self.weights);
+        parameters
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        if parameters.len() != self.biases.len() + self.weights.len() {
+            panic!("The number of parameters ({}) doesn't match the convolution ({})", parameters.len(), self.biases.len() + self.weights.len());
+        }
+        let bias_count = self.biases.len();
+        self.biases = parameters[..bias_count].to_vec();
+        self.weights = parameters[bias_count..].to_vec();
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+        let mut gradients = self.bias_adjusts.clone();
+        gradients.extend_from_slice(&self.weight_adjusts);
+        gradients
+    }
+
+    fn adjust(&mut self, learning_rate: f32) {
+        for num in 0..self.biases.len() {
+            self.biases[num] -= self.bias_adjusts[num] * learning_rate;
+            self.bias_adjusts[num] = 0.0;
+        }
+        for num in 0..self.weights.len() {
+            self.weights[num] -= self.weight_adjusts[num] * learning_rate;
+            self.weight_adjusts[num] = 0.0;
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("conv2d {} {} {} {} {} {} {} {} {} {}", self.in_channels, self.out_channels, self.input_size.0, self.input_size.1,
+                self.kernel_size.0, self.kernel_size.1, self.stride.0, self.stride.1, self.padding.0, self.padding.1)
+    }
+}
+
+#[derive(Clone)]
+pub struct Conv1d {
+//A 1D convolution over channels of sequences. It is a Conv2d with a height of 1.
+    pub conv: Conv2d,
+}
+
+impl Conv1d {
+    pub fn new(in_channels: usize, out_channels: usize, input_length: usize, kernel_size: usize, stride: usize, padding: usize) -> Conv1d {
+        Conv1d {
+            conv: Conv2d::with_sizes(in_channels, out_channels, (1, input_length), (1, kernel_size), (1, stride), (0, padding)),
+        }
+    }
+
+    pub fn from_rng(in_channels: usize, out_channels: usize, input_length: usize, kernel_size: usize, stride: usize, padding: usize, rng: &mut SimpleRng) -> Conv1d {
+    //Like new, but the random biases and weights come from rng.
+        Conv1d {
+            conv: Conv2d::from_rng(in_channels, out_channels, (1, input_length), (1, kernel_size), (1, stride), (0, padding), rng),
+        }
+    }
+
+    pub fn output_length(&self) -> usize {
+    //The length of each output channel.
+        self.conv.output_size().1
+    }
+}
+
+impl Module for Conv1d {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.conv.forward(inputs)
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        self.conv.forward_into(inputs, outputs)
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        self.conv.output_count(input_count)
+    }
+
+    fn backward(&mut self, inputs: &[f32], outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        self.conv.backward(inputs, outputs, output_deltas)
+    }
+
+    fn parameters(&self) -> Vec<f32> {
+        self.conv.parameters()
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        self.conv.set_parameters(parameters)
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+        self.conv.gradients()
+    }
+
+    fn adjust(&mut self, learning_rate: f32) {
+        self.conv.adjust(learning_rate)
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("conv1d {} {} {} {} {} {}", self.conv.in_channels, self.conv.out_channels, self.conv.input_size.1,
+                self.conv.kernel_size.1, self.conv.stride.1, self.conv.padding.1)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Pooling {
+    Max,
+    Average,
+}
+
+#[derive(Clone)]
+struct Pool2d {
+//The shared part of MaxPool2d and AvgPool2d. Every channel is pooled on its own, and the padding is ignored rather than counted as zeros.
+    pooling: Pooling,
+    channels: usize,
+    input_size: (usize, usize),
+    pool_size: usize,
+    stride: usize,
+    padding: usize,
+}
+
+impl Pool2d {
+    fn new(pooling: Pooling, channels: usize, input_size: (usize, usize), pool_size: usize, stride: usize, padding: usize) -> Pool2d {
+        if stride == 0 {
+            panic!("The stride can't be 0");
+        }
+        if 2 * padding > pool_size {
+            panic!("The padding ({}) can't be more than half the pool size ({})", padding, pool_size);
+        }
+        let pool = Pool2d {
+            pooling,
+            channels,
+            input_size,
+            pool_size,
+            stride,
+            padding,
+        };
+        pool.output_size();
+        pool
+    }
+
+    fn output_size(&self) -> (usize, usize) {
+        (output_length(self.input_size.0, self.pool_size, self.stride, self.padding),
+         output_length(self.input_size.1, self.pool_size, self.stride, self.padding))
+    }
+
+    fn describe_sizes(&self) -> String {
+        format!("{} {} {} {} {} {}", self.channels, self.input_size.0, self.input_size.1, self.pool_size, self.stride, self.padding)
+    }
+
+    fn window(&self, channel: usize, out_y: usize, out_x: usize) -> Vec<usize> {
+    //The positions in the inputs covered by one output.
+        let (height, width) = self.input_size;
+        let mut positions = Vec::new();
+        for ky in 0..self.pool_size {
+            let y = match input_position(out_y, ky, self.stride, self.padding, height) {Some(y) => y, None => continue};
+            for kx in 0..self.pool_size {
+                let x = match input_position(out_x, kx, self.stride, self.padding, width) {Some(x) => x, None => continue};
+                positions.push((channel * height + y) * width + x);
+            }
+        }
+        positions
+    }
+
+    fn output_count(&self) -> usize {
+        let (output_height, output_width) = self.output_size();
+        self.channels * output_height * output_width
+    }
+
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut values = vec![0.0; self.output_count()];
+        self.forward_into(inputs, &mut values);
+        values
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+    //Goes through the same positions as window, without collecting them, so nothing is allocated.
+        let input_len = self.channels * self.input_size.0 * self.input_size.1;
+        if inputs.len() != input_len {
+            panic!("The pooling expects {} values ({} channels of {:?}), but received {}", input_len, self.channels, self.input_size, inputs.len());
+        }
+        let (height, width) = self.input_size;
+        let (output_height, output_width) = self.output_size();
+        let mut output_num = 0;
+        for channel in 0..self.channels {
+            for out_y in 0..output_height {
+                for out_x in 0..output_width {
+                    let mut max = f32::NEG_INFINITY;
+                    let mut sum = 0.0;
+                    let mut count = 0;
+                    for ky in 0..self.pool_size {
+                        let y = match input_position(out_y, ky, self.stride, self.padding, height) {Some(y) => y, None => continue};
+                        for kx in 0..self.pool_size {
+                            let x = match input_position(out_x, kx, self.stride, self.padding, width) {Some(x) => x, None => continue};
+                            let value = inputs[(channel * height + y) * width + x];
+                            max = max.max(value);
+                            sum += value;
+                            count += 1;
+                        }
+                    }
+                    outputs[output_num] = match self.pooling {
+                        Pooling::Max => max,
+                        Pooling::Average => sum / count as f32,
+                    };
+                    output_num += 1;
+                }
+            }
+        }
+    }
+
+    fn backward(&self, inputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+    //Max pooling sends each delta back to the input that was largest, average pooling splits it evenly.
+        let (output_height, output_width) = self.output_size();
+        let mut input_deltas = vec![0.0; inputs.len()];
+        for channel in 0..self.channels {
+            for out_y in 0..output_height {
+                for out_x in 0..output_width {
+                    let delta = output_deltas[(channel * output_height + out_y) * output_width + out_x];
+                    let window = self.window(channel, out_y, out_x);
+                    match self.pooling {
+                        Pooling::Max => {
+                            let mut max_num = window[0];
+                            for &num in window.iter() {
+                                if inputs[num] > inputs[max_num] {
+                                    max_num = num;
+                                }
+                            }
+                            input_deltas[max_num] += delta;
+                        },
+                        Pooling::Average => {
+                            for &num in window.iter() {
+                                input_deltas[num] += delta / window.len() as f32;
+                            }
+                        },
+                    }
+                }
+            }
+        }
+        input_deltas
+    }
+}
+
+#[derive(Clone)]
+pub struct MaxPool2d {
+//Keeps the largest value in each pool_size x pool_size window of every channel.
+    pool: Pool2d,
+}
+
+impl MaxPool2d {
+    pub fn new(channels: usize, input_size: (usize, usize), pool_size: usize, stride: usize, padding: usize) -> MaxPool2d {
+        MaxPool2d {
+            pool: Pool2d::new(Pooling::Max, channels, input_size, pool_size, stride, padding),
+        }
+    }
+
+    pub fn output_size(&self) -> (usize, usize) {
+    //The (height, width) of each output channel.
+        self.pool.output_size()
+    }
+}
+
+impl Module for MaxPool2d {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.pool.forward(inputs)
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        self.pool.forward_into(inputs, outputs)
+    }
+
+    fn output_count(&self, _input_count: usize) -> usize {
+        self.pool.output_count()
+    }
+
+    fn backward(&mut self, inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        self.pool.backward(inputs, output_deltas)
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("maxpool2d {}", self.pool.describe_sizes())
+    }
+}
+
+#[derive(Clone)]
+pub struct AvgPool2d {
+//Keeps the average of each pool_size x pool_size window of every channel.
+    pool: Pool2d,
+}
+
+impl AvgPool2d {
+    pub fn new(channels: usize, input_size: (usize, usize), pool_size: usize, stride: usize, padding: usize) -> AvgPool2d {
+        AvgPool2d {
+            pool: Pool2d::new(Pooling::Average, channels, input_size, pool_size, stride, padding),
+        }
+    }
+
+    pub fn output_size(&self) -> (usize, usize) {
+    //The (height, width) of each output channel.
+        self.pool.output_size()
+    }
+}
+
+impl Module for AvgPool2d {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.pool.forward(inputs)
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        self.pool.forward_into(inputs, outputs)
+    }
+
+    fn output_count(&self, _input_count: usize) -> usize {
+        self.pool.output_count()
+    }
+
+    fn backward(&mut self, inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        self.pool.backward(inputs, output_deltas)
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("avgpool2d {}", self.pool.describe_sizes())
+    }
+}
+
+#[derive(Clone)]
+pub struct Flatten {
+//Turns channels of images into one row of values, so a dense Layer can follow a convolution.
+//The values are already stored in one list, so only the size is checked.
+    pub input_shape: Vec<usize>,
+}
+
+impl Flatten {
+    pub fn new(input_shape: Vec<usize>) -> Flatten {
+        Flatten {
+            input_shape,
+        }
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.input_shape.iter().product()
+    }
+}
+
+impl Module for Flatten {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        if inputs.len() != self.output_len() {
+            panic!("Expected {} values with the shape {:?}, but received {}", self.output_len(), self.input_shape, inputs.len());
+        }
+        inputs.to_vec()
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        if inputs.len() != self.output_len() {
+            panic!("Expected {} values with the shape {:?}, but received {}", self.output_len(), self.input_shape, inputs.len());
+        }
+        outputs.copy_from_slice(inputs);
+    }
+
+    fn output_count(&self, _input_count: usize) -> usize {
+        self.output_len()
+    }
+
+    fn backward(&mut self, _inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        output_deltas.to_vec()
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("flatten {}", shape_text(&self.input_shape))
+    }
+
+    fn passes_values_on(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes_layers::tests::{check_gradients, test_inputs};
+
+    fn distinct_inputs(count: usize) -> Vec<f32> {
+    //Inputs without ties, so the largest value of every pooling window stays the largest when it is moved a little.
+        (0..count).map(|num| ((num * 7) % count) as f32 * 0.1 - 1.0).collect()
+    }
+
+    #[test]
+    fn conv2d_gradients_match_finite_differences() {
+        let mut conv = Conv2d::from_rng(2, 3, (5, 4), (3, 2), (2, 1), (1, 1), &mut SimpleRng::new(1));
+        check_gradients(&mut conv, &test_inputs(2 * 5 * 4), 1e-2);
+    }
+
+    #[test]
+    fn conv1d_gradients_match_finite_differences() {
+        let mut conv = Conv1d::from_rng(2, 2, 7, 3, 2, 1, &mut SimpleRng::new(2));
+        check_gradients(&mut conv, &test_inputs(2 * 7), 1e-2);
+    }
+
+    #[test]
+    fn the_same_rng_gives_the_same_convolution() {
+        let first = Conv2d::from_rng(2, 3, (5, 4), (3, 3), (1, 1), (1, 1), &mut SimpleRng::new(5));
+        let second = Conv2d::from_rng(2, 3, (5, 4), (3, 3), (1, 1), (1, 1), &mut SimpleRng::new(5));
+        assert_eq!(first.parameters(), second.parameters());
+        assert!(first.weights.iter().all(|weight| (-1.0..1.0).contains(weight)));
+    }
+
+    #[test]
+    fn max_pooling_keeps_the_largest_values() {
+        let pool = MaxPool2d::new(1, (4, 4), 2, 2, 0);
+        let inputs: Vec<f32> = (0..16).map(|num| num as f32).collect();
+        assert_eq!(pool.output_size(), (2, 2));
+        assert_eq!(pool.forward(&inputs), vec![5.0, 7.0, 13.0, 15.0]);
+        let mut pool = MaxPool2d::new(2, (4, 4), 3, 1, 1);
+        check_gradients(&mut pool, &distinct_inputs(2 * 16), 1e-2);
+    }
+
+    #[test]
+    fn average_pooling_ignores_the_padding() {
+        let pool = AvgPool2d::new(1, (2, 2), 2, 1, 1);
+        assert_eq!(pool.output_size(), (3, 3));
+        assert_eq!(pool.forward(&[1.0, 2.0, 3.0, 4.0]), vec![1.0, 1.5, 2.0, 2.0, 2.5, 3.0, 3.0, 3.5, 4.0]);
+        let mut pool = AvgPool2d::new(2, (5, 5), 3, 2, 1);
+        check_gradients(&mut pool, &test_inputs(2 * 25), 1e-2);
+    }
+
+    #[test]
+    fn flatten_passes_values_and_deltas_on() {
+        let mut flatten = Flatten::new(vec![2, 3, 4]);
+        let inputs = test_inputs(24);
+        assert_eq!(flatten.output_count(24), 24);
+        assert_eq!(flatten.forward(&inputs), inputs);
+        assert_eq!(flatten.backward(&inputs, &inputs, &distinct_inputs(24)), distinct_inputs(24));
+    }
+}
@@ -0,0 +1,339 @@
+use crate::rng::SimpleRng;
+
+#[derive(Clone)]
+pub struct Dataset {
+//Samples to train or test a Network on. inputs[num] should give the outputs in outputs[num].
+    pub inputs: Vec<Vec<f32>>,
+    pub outputs: Vec<Vec<f32>>,
+}
+
+impl Dataset {
+    pub fn new(inputs: Vec<Vec<f32>>, outputs: Vec<Vec<f32>>) -> Dataset {
+        if inputs.len() != outputs.len() {
+            panic!("The number of inputs ({}) doesn't match the number of outputs ({})", inputs.len(), outputs.len());
+        }
+        Dataset {
+            inputs,
+            outputs,
+        }
+    }
+
+    pub fn from_labels(inputs: Vec<Vec<f32>>, labels: &[usize], class_count: usize) -> Dataset {
+    //Makes a dataset for classification, where each output is the one-hot encoding of a label.
+        Dataset::new(inputs, one_hot_labels(labels, class_count))
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+pub fn one_hot(label: usize, class_count: usize) -> Vec<f32> {
+//Turns a class number into a list of zeros with a single 1 at the position of the class, e.g. 2 of 4 into [0, 0, 1, 0].
+    if label >= class_count {
+        panic!("The label {} is outside the {} classes", label, class_count);
+    }
+    let mut values = vec![0.0; class_count];
+    values[label] = 1.0;
+    values
+}
+
+pub fn one_hot_labels(labels: &[usize], class_count: usize) -> Vec<Vec<f32>> {
+    let mut encoded = Vec::new();
+    for num in 0..labels.len() {
+        encoded.push(one_hot(labels[num], class_count));
+    }
+    encoded
+}
+
+pub fn decode_one_hot(values: &[f32]) -> usize {
+//The position of the largest value, which is the class a one-hot encoding or a list of probabilities points to.
+    if values.is_empty() {
+        panic!("Can't find the class of an empty list");
+    }
+    let mut best = 0;
+    for num in 1..values.len() {
+        if values[num] > values[best] {
+            best = num;
+        }
+    }
+    best
+}
+
+#[derive(Clone, Debug)]
+pub struct LabelEncoder {
+//Gives each distinct text label a class number, in the order they were first seen.
+    pub classes: Vec<String>,
+}
+
+impl LabelEncoder {
+    pub fn fit(labels: &[String]) -> LabelEncoder {
+        let mut classes: Vec<String> = Vec::new();
+        for label in labels.iter() {
+            if !classes.contains(label) {
+                classes.push(label.clone());
+            }
+        }
+        LabelEncoder {
+            classes,
+        }
+    }
+
+    pub fn encode(&self, label: &str) -> usize {
+        match self.classes.iter().position(|class| class == label) {
+            Some(class) => class,
+            None => panic!("The label {:?} wasn't seen when the encoder was fitted", label),
+        }
+    }
+
+    pub fn decode(&self, class: usize) -> &str {
+        &self.classes[class]
+    }
+
+    pub fn one_hot(&self, label: &str) -> Vec<f32> {
+        one_hot(self.encode(label), self.classes.len())
+    }
+}
+
+//Generators for small standard problems, for demos and for trying out the library.
+//The truth tables always give the same samples. The others take an rng, so the same seed gives the same samples.
+//Problems with two classes have a single output that should be 0 or 1, the rest have a one-hot output per class.
+
+pub const GENERATOR_NAMES: [&str; 11] = ["seven_segment", "decimal", "xor", "and", "or", "parity", "moons", "circles", "spirals", "blobs", "sine"];
+
+pub fn generate(name: &str, count: usize, rng: &mut SimpleRng) -> Option<Dataset> {
+//The dataset with the given name, with count samples for the generators that are not truth tables.
+//"parity" is 4 bit parity, and a number can be added for other sizes, e.g. "parity6".
+    let dataset = match name {
+        "seven_segment" => seven_segment(),
+        "decimal" => binary_to_decimal(),
+        "xor" => xor(),
+        "and" => and(),
+        "or" => or(),
+        "parity" => parity(4),
+        "moons" => two_moons(count, 0.1, rng),
+        "circles" => circles(count, 0.08, rng),
+        "spirals" => spirals(count, 2, 0.05, rng),
+        "blobs" => gaussian_blobs(count, 3, 0.5, rng),
+        "sine" => sine(count, 0.05, rng),
+        _ if name.starts_with("parity") => match name["parity".len()..].parse() {
+            Ok(bits) if bits > 0 && bits <= 16 => parity(bits),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(dataset)
+}
+
+pub fn binary_inputs(bits: usize) -> Vec<Vec<f32>> {
+//Every number that fits in the bits, in order, written in binary with the most significant bit first.
+    let mut inputs = Vec::new();
+    for number in 0..(1usize << bits) {
+        let mut values = Vec::new();
+        for bit in (0..bits).rev() {
+            values.push(((number >> bit) & 1) as f32);
+        }
+        inputs.push(values);
+    }
+    inputs
+}
+
+fn truth_table(bits: usize, rule: &dyn Fn(usize) -> bool) -> Dataset {
+    let mut outputs = Vec::new();
+    for number in 0..(1usize << bits) {
+        outputs.push(vec![if rule(number) {1.0} else {0.0}]);
+    }
+    Dataset::new(binary_inputs(bits), outputs)
+}
+
+pub fn xor() -> Dataset {
+    truth_table(2, &|number| number == 1 || number == 2)
+}
+
+pub fn and() -> Dataset {
+    truth_table(2, &|number| number == 3)
+}
+
+pub fn or() -> Dataset {
+    truth_table(2, &|number| number != 0)
+}
+
+pub fn parity(bits: usize) -> Dataset {
+//The output is 1 when an odd number of the bits are 1. XOR is parity with 2 bits.
+    truth_table(bits, &|number| number.count_ones() % 2 == 1)
+}
+
+pub fn seven_segment() -> Dataset {
+//The numbers 0-15 in binary, and how they are shown on a 7-segment display: "[1   8]" [B, C,     A, B, C, D, E, F, G]
+    let outputs: [[f32; 9]; 16] = [
+        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0], // 0
+        [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0],
+        [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0],
+        [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0], // 5
+        [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0], //10
+        [1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
+        [1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0],
+        [1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0],
+        [1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0], // 15
+    ] ;
+    Dataset::new(binary_inputs(4), outputs.iter().map(|row| row.to_vec()).collect())
+}
+
+pub fn binary_to_decimal() -> Dataset {
+//The numbers 0-15 in binary, each with its own class, so the network has to tell which number it is.
+    let labels: Vec<usize> = (0..16).collect();
+    Dataset::from_labels(binary_inputs(4), &labels, 16)
+}
+
+fn binary_labels(points: Vec<Vec<f32>>, labels: Vec<usize>) -> Dataset {
+    let outputs = labels.iter().map(|label| vec![*label as f32]).collect();
+    Dataset::new(points, outputs)
+}
+
+pub fn two_moons(count: usize, noise: f32, rng: &mut SimpleRng) -> Dataset {
+//Two interleaving half circles, one for each class. noise is the standard deviation of the noise added to each point.
+    let mut points = Vec::new();
+    let mut labels = Vec::new();
+    for num in 0..count {
+        let label = num % 2;
+        let angle = rng.gen_range(0.0, std::f32::consts::PI);
+        let (x, y) = if label == 0 {(angle.cos(), angle.sin())} else {(1.0 - angle.cos(), 0.5 - angle.sin())};
+        points.push(vec![x + noise * rng.gen_normal(), y + noise * rng.gen_normal()]);
+        labels.push(label);
+    }
+    binary_labels(points, labels)
+}
+
+pub fn circles(count: usize, noise: f32, rng: &mut SimpleRng) -> Dataset {
+//A circle with radius 0.5 inside a circle with radius 1. The points of the inner circle have output 1.
+    let mut points = Vec::new();
+    let mut labels = Vec::new();
+    for num in 0..count {
+        let label = num % 2;
+        let radius = if label == 1 {0.5} else {1.0};
+        let angle = rng.gen_range(0.0, 2.0 * std::f32::consts::PI);
+        points.push(vec![radius * angle.cos() + noise * rng.gen_normal(), radius * angle.sin() + noise * rng.gen_normal()]);
+        labels.push(label);
+    }
+    binary_labels(points, labels)
+}
+
+pub fn spirals(count: usize, arms: usize, noise: f32, rng: &mut SimpleRng) -> Dataset {
+//Spirals winding out from the middle, one class for each arm.
+    let mut points = Vec::new();
+    let mut labels = Vec::new();
+    for num in 0..count {
+        let label = num % arms;
+        let distance = rng.gen_f32(); // 0 in the middle and 1 at the end of the arm
+        let angle = distance * 3.0 * std::f32::consts::PI + label as f32 * 2.0 * std::f32::consts::PI / arms as f32;
+        points.push(vec![distance * angle.cos() + noise * rng.gen_normal(), distance * angle.sin() + noise * rng.gen_normal()]);
+        labels.push(label);
+    }
+    Dataset::from_labels(points, &labels, arms)
+}
+
+pub fn gaussian_blobs(count: usize, classes: usize, spread: f32, rng: &mut SimpleRng) -> Dataset {
+//Round clouds of points, one for each class, with their middles evenly spaced on a circle with radius 2.
+//spread is the standard deviation of the points around the middle.
+    let mut points = Vec::new();
+    let mut labels = Vec::new();
+    for num in 0..count {
+        let label = num % classes;
+        let angle = label as f32 * 2.0 * std::f32::consts::PI / classes as f32;
+        points.push(vec![2.0 * angle.cos() + spread * rng.gen_normal(), 2.0 * angle.sin() + spread * rng.gen_normal()]);
+        labels.push(label);
+    }
+    Dataset::from_labels(points, &labels, classes)
+}
+
+pub fn sine(count: usize, noise: f32, rng: &mut SimpleRng) -> Dataset {
+//Regression of one period of a sine wave, from x in [-pi; pi[.
+//The output is scaled from [-1; 1] to [0; 1], so a sigmoid output layer can reach it.
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    for _ in 0..count {
+        let x = rng.gen_range(-std::f32::consts::PI, std::f32::consts::PI);
+        inputs.push(vec![x]);
+        outputs.push(vec![(x.sin() + 1.0) / 2.0 + noise * rng.gen_normal()]);
+    }
+    Dataset::new(inputs, outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_hot_round_trips() {
+        assert_eq!(one_hot(2, 4), vec![0.0, 0.0, 1.0, 0.0]);
+        for label in 0..5 {
+            assert_eq!(decode_one_hot(&one_hot(label, 5)), label);
+        }
+        let labels = [1, 0, 2, 2];
+        let decoded: Vec<usize> = one_hot_labels(&labels, 3).iter().map(|values| decode_one_hot(values)).collect();
+        assert_eq!(decoded, labels);
+        assert_eq!(decode_one_hot(&[0.1, 0.7, 0.2]), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn one_hot_outside_the_classes_panics() {
+        one_hot(3, 3);
+    }
+
+    #[test]
+    fn label_encoder_round_trips() {
+        let labels: Vec<String> = ["cat", "dog", "cat", "bird", "dog"].iter().map(|label| label.to_string()).collect();
+        let encoder = LabelEncoder::fit(&labels);
+        assert_eq!(encoder.classes, vec!["cat", "dog", "bird"]);
+        for label in labels.iter() {
+            assert_eq!(encoder.decode(encoder.encode(label)), label);
+            assert_eq!(decode_one_hot(&encoder.one_hot(label)), encoder.encode(label));
+        }
+        assert_eq!(encoder.one_hot("bird"), vec![0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn generators_repeat_with_the_same_seed() {
+        let random = ["moons", "circles", "spirals", "blobs", "sine"];
+        for name in GENERATOR_NAMES.iter() {
+            let first = generate(name, 50, &mut SimpleRng::new(7)).unwrap();
+            let again = generate(name, 50, &mut SimpleRng::new(7)).unwrap();
+            let other = generate(name, 50, &mut SimpleRng::new(8)).unwrap();
+            assert_eq!(first.inputs, again.inputs, "{}", name);
+            assert_eq!(first.outputs, again.outputs, "{}", name);
+            if random.contains(name) {
+                assert_ne!(first.inputs, other.inputs, "{}", name);
+            } else { // The truth tables don't depend on the seed
+                assert_eq!(first.inputs, other.inputs, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn generators_give_samples_of_one_width() {
+        for name in GENERATOR_NAMES.iter() {
+            let data = generate(name, 30, &mut SimpleRng::new(1)).unwrap();
+            assert!(!data.is_empty(), "{}", name);
+            assert_eq!(data.inputs.len(), data.outputs.len(), "{}", name);
+            for num in 0..data.len() {
+                assert_eq!(data.inputs[num].len(), data.inputs[0].len(), "{}", name);
+                assert_eq!(data.outputs[num].len(), data.outputs[0].len(), "{}", name);
+            }
+        }
+        assert_eq!(generate("parity6", 0, &mut SimpleRng::new(1)).unwrap().inputs[0].len(), 6);
+        assert!(generate("parity17", 0, &mut SimpleRng::new(1)).is_none());
+        assert!(generate("spiral", 10, &mut SimpleRng::new(1)).is_none());
+    }
+}
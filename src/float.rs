@@ -0,0 +1,70 @@
+//The float functions the inference core uses. With std they are the methods of f32,
+//and without std they come from libm, which gives the same values to within a bit or so.
+
+#[cfg(feature = "std")]
+pub fn exp(value: f32) -> f32 {
+    value.exp()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn exp(value: f32) -> f32 {
+    libm::expf(value)
+}
+
+#[cfg(feature = "std")]
+pub fn tanh(value: f32) -> f32 {
+    value.tanh()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn tanh(value: f32) -> f32 {
+    libm::tanhf(value)
+}
+
+#[cfg(feature = "std")]
+pub fn ln(value: f32) -> f32 {
+    value.ln()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn ln(value: f32) -> f32 {
+    libm::logf(value)
+}
+
+#[cfg(feature = "std")]
+pub fn sqrt(value: f32) -> f32 {
+    value.sqrt()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn sqrt(value: f32) -> f32 {
+    libm::sqrtf(value)
+}
+
+#[cfg(feature = "std")]
+pub fn cos(value: f32) -> f32 {
+    value.cos()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn cos(value: f32) -> f32 {
+    libm::cosf(value)
+}
+
+pub fn square(value: f32) -> f32 {
+    value * value
+}
+
+#[cfg(feature = "std")]
+pub fn round(value: f32) -> f32 {
+    value.round()
+}
+
+#[cfg(not(feature = "std"))]
+pub fn round(value: f32) -> f32 {
+    libm::roundf(value)
+}
+
+pub fn abs(value: f32) -> f32 {
+    if value < 0.0 {-value} else {value}
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+//A small animated GIF encoder, so training animations can be made without any image crates.
+//Every frame is reduced to the same fixed palette of 256 colours: a 6x6x6 colour cube plus 40 extra greys,
+//which keeps the result the same from run to run and suits the mostly grey drawings of the network.
+
+const CUBE_LEVELS: u32 = 6;
+const GREY_COUNT: u32 = 40;
+
+pub fn save_gif(path: &Path, width: usize, height: usize, frames: &[Vec<u8>], delay: u16) -> io::Result<()> {
+//Saves the frames as a looping animated GIF. See encode_gif.
+    fs::write(path, encode_gif(width, height, frames, delay))
+}
+
+pub fn encode_gif(width: usize, height: usize, frames: &[Vec<u8>], delay: u16) -> Vec<u8> {
+//Each frame is width * height pixels of 4 bytes, red, green, blue and alpha, starting at the top left. Alpha is ignored.
+//delay is the time each frame is shown in hundredths of a second.
+    if width > 0xFFFF || height > 0xFFFF {
+        panic!("A GIF can't be larger than 65535x65535 pixels, but the frames are {}x{}", width, height);
+    }
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"GIF89a");
+    push_u16(&mut bytes, width as u16);
+    push_u16(&mut bytes, height as u16);
+    bytes.push(0xF7); //A global colour table of 256 colours with 8 bits per channel.
+    bytes.push(0); //Background colour
+    bytes.push(0); //Pixel aspect ratio
+    for colour in palette().iter() {
+        bytes.extend_from_slice(colour);
+    }
+    //The Netscape extension that makes the animation loop forever.
+    bytes.extend_from_slice(&[0x21, 0xFF, 0x0B]);
+    bytes.extend_from_slice(b"NETSCAPE2.0");
+    bytes.extend_from_slice(&[0x03, 0x01, 0x00, 0x00, 0x00]);
+    for frame in frames.iter() {
+        if frame.len() != width * height * 4 {
+            panic!("A frame has {} bytes, but {}x{} pixels need {}", frame.len(), width, height, width * height * 4);
+        }
+        bytes.extend_from_slice(&[0x21, 0xF9, 0x04, 0x04]); //Graphic control extension. The frame is left in place for the next one to cover.
+        push_u16(&mut bytes, delay);
+        bytes.extend_from_slice(&[0x00, 0x00]);
+        bytes.push(0x2C); //Image descriptor, covering the whole screen without a colour table of its own.
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, 0);
+        push_u16(&mut bytes, width as u16);
+        push_u16(&mut bytes, height as u16);
+        bytes.push(0);
+        let mut indices = Vec::with_capacity(width * height);
+        for pixel in frame.chunks(4) {
+            indices.push(palette_index(pixel[0], pixel[1], pixel[2]));
+        }
+        bytes.push(8); //The smallest code size of the compressed data.
+        let data = compress(&indices);
+        for block in data.chunks(255) {
+            bytes.push(block.len() as u8);
+            bytes.extend_from_slice(block);
+        }
+        bytes.push(0);
+    }
+    bytes.push(0x3B);
+    bytes
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.push((value & 0xFF) as u8);
+    bytes.push((value >> 8) as u8);
+}
+
+fn cube_value(level: u32) -> u8 {
+    (level * 255 / (CUBE_LEVELS - 1)) as u8
+}
+
+fn grey_value(num: u32) -> u8 {
+    ((num + 1) * 255 / (GREY_COUNT + 1)) as u8
+}
+
+fn palette() -> Vec<[u8; 3]> {
+    let mut colours = Vec::new();
+    for red in 0..CUBE_LEVELS {
+        for green in 0..CUBE_LEVELS {
+            for blue in 0..CUBE_LEVELS {
+                colours.push([cube_value(red), cube_value(green), cube_value(blue)]);
+            }
+        }
+    }
+    for num in 0..GREY_COUNT {
+        let grey = grey_value(num);
+        colours.push([grey, grey, grey]);
+    }
+    colours
+}
+
+fn palette_index(red: u8, green: u8, blue: u8) -> u8 {
+//The closest colour of the palette, either in the colour cube or among the greys.
+    let level = |value: u8| (value as u32 * (CUBE_LEVELS - 1) + 127) / 255;
+    let (red_level, green_level, blue_level) = (level(red), level(green), level(blue));
+    let cube = [cube_value(red_level), cube_value(green_level), cube_value(blue_level)];
+    let average = (red as u32 + green as u32 + blue as u32) / 3;
+    let grey_num = ((average * (GREY_COUNT + 1) + 127) / 255).clamp(1, GREY_COUNT) - 1;
+    let grey = grey_value(grey_num);
+    let distance = |colour: [u8; 3]| {
+        let mut sum = 0;
+        for (value, target) in colour.iter().zip([red, green, blue].iter()) {
+            let difference = *value as i32 - *target as i32;
+            sum += difference * difference;
+        }
+        sum
+    };
+    if distance([grey, grey, grey]) < distance(cube) {
+        (CUBE_LEVELS * CUBE_LEVELS * CUBE_LEVELS + grey_num) as u8
+    } else {
+        ((red_level * CUBE_LEVELS + green_level) * CUBE_LEVELS + blue_level) as u8
+    }
+}
+
+struct BitWriter {
+//Packs codes of varying length into bytes, starting with the lowest bits, the way GIF wants them.
+    bytes: Vec<u8>,
+    buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= (code as u32) << self.bit_count;
+        self.bit_count += size;
+        while self.bit_count >= 8 {
+            self.bytes.push((self.buffer & 0xFF) as u8);
+            self.buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push((self.buffer & 0xFF) as u8);
+        }
+        self.bytes
+    }
+}
+
+fn compress(indices: &[u8]) -> Vec<u8> {
+//LZW compression with 8 bit colour indices. Codes start at 9 bits and grow to at most 12,
+//after which the table is cleared and started over.
+    let clear_code: u16 = 256;
+    let end_code: u16 = 257;
+    let mut writer = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bit_count: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = 9;
+    let mut next_code = 258;
+    writer.write(clear_code, size);
+    if indices.is_empty() {
+        writer.write(end_code, size);
+        return writer.finish()
+    }
+    let mut prefix = indices[0] as u16;
+    for num in 1..indices.len() {
+        let index = indices[num];
+        if let Some(code) = table.get(&(prefix, index)) {
+            prefix = *code;
+            continue
+        }
+        writer.write(prefix, size);
+        if next_code < 4096 {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+            if next_code > (1 << size) && size < 12 {
+                size += 1;
+            }
+        } else {
+            writer.write(clear_code, size);
+            table.clear();
+            size = 9;
+            next_code = 258;
+        }
+        prefix = index as u16;
+    }
+    writer.write(prefix, size);
+    //The decoder adds a table entry for the last code as well, which can make the end code one bit longer.
+    if next_code == (1 << size) && size < 12 {
+        size += 1;
+    }
+    writer.write(end_code, size);
+    writer.finish()
+}
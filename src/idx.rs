@@ -0,0 +1,146 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::dataset::{self, Dataset};
+
+//Reads files in the IDX format, which is what the MNIST handwritten digits come in.
+//An IDX file starts with two zero bytes, a byte for the type of the values and a byte for the number of dimensions,
+//followed by the size of each dimension as a big-endian u32, and then the values themselves, also big-endian.
+//The files have to be unpacked first, e.g. with gunzip train-images-idx3-ubyte.gz
+
+#[derive(Clone, Debug)]
+pub struct IdxArray {
+    pub dimensions: Vec<usize>,
+    pub values: Vec<f32>, //All the values in order, with the last dimension changing fastest.
+}
+
+impl IdxArray {
+    pub fn item_size(&self) -> usize {
+    //The number of values in each item, e.g. 784 for 28x28 images.
+        self.dimensions[1..].iter().product()
+    }
+}
+
+pub fn read_idx(path: &Path) -> io::Result<IdxArray> {
+    let bytes = fs::read(path)?;
+    if bytes.len() < 4 || bytes[0] != 0 || bytes[1] != 0 {
+        return Err(invalid(format!("{} isn't an IDX file", path.display())))
+    }
+    let value_size: usize = match bytes[2] {
+        0x08 | 0x09 => 1,
+        0x0B => 2,
+        0x0C | 0x0D => 4,
+        0x0E => 8,
+        other => return Err(invalid(format!("Unknown IDX value type 0x{:02X} in {}", other, path.display()))),
+    };
+    let dimension_count = bytes[3] as usize;
+    if dimension_count == 0 {
+        return Err(invalid(format!("{} has no dimensions", path.display())))
+    }
+    let start = 4 + 4 * dimension_count;
+    if bytes.len() < start {
+        return Err(invalid(format!("{} ended in the middle of its dimensions", path.display())))
+    }
+    let mut dimensions = Vec::new();
+    for num in 0..dimension_count {
+        let position = 4 + 4 * num;
+        dimensions.push(u32::from_be_bytes([bytes[position], bytes[position + 1], bytes[position + 2], bytes[position + 3]]) as usize);
+    }
+    //The sizes come from the file, so their product can be too big for a usize.
+    let mut byte_count = Some(value_size);
+    for size in dimensions.iter() {
+        byte_count = byte_count.and_then(|bytes| bytes.checked_mul(*size));
+    }
+    let byte_count = match byte_count {
+        Some(byte_count) => byte_count,
+        None => return Err(invalid(format!("The dimensions {:?} in {} are too big", dimensions, path.display()))),
+    };
+    let count = byte_count / value_size;
+    if bytes.len() - start != byte_count {
+        return Err(invalid(format!("{} should have {} bytes of values, but has {}", path.display(), byte_count, bytes.len() - start)))
+    }
+    let mut values = Vec::with_capacity(count);
+    for chunk in bytes[start..].chunks(value_size) {
+        let value = match bytes[2] {
+            0x08 => chunk[0] as f32,
+            0x09 => chunk[0] as i8 as f32,
+            0x0B => i16::from_be_bytes([chunk[0], chunk[1]]) as f32,
+            0x0C => i32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as f32,
+            0x0D => f32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+            _ => f64::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3], chunk[4], chunk[5], chunk[6], chunk[7]]) as f32,
+        };
+        values.push(value);
+    }
+    Ok(IdxArray {
+        dimensions,
+        values,
+    })
+}
+
+pub fn load_mnist(images_path: &Path, labels_path: &Path) -> io::Result<Dataset> {
+//Makes a dataset from an image file and a label file, like train-images-idx3-ubyte and train-labels-idx1-ubyte.
+//The pixels are scaled from 0-255 to 0-1, and the labels are one-hot encoded with 10 classes.
+    let images = read_idx(images_path)?;
+    let labels = read_idx(labels_path)?;
+    if images.dimensions.len() < 2 {
+        return Err(invalid(format!("{} should have a dimension for the images and at least one for the pixels", images_path.display())))
+    }
+    if labels.dimensions.len() != 1 {
+        return Err(invalid(format!("{} should be a list of labels", labels_path.display())))
+    }
+    if images.dimensions[0] != labels.dimensions[0] {
+        return Err(invalid(format!("There are {} images but {} labels", images.dimensions[0], labels.dimensions[0])))
+    }
+    let pixel_count = images.item_size();
+    if pixel_count == 0 {
+        return Err(invalid(format!("The images in {} have no pixels", images_path.display())))
+    }
+    let mut inputs = Vec::new();
+    for image in images.values.chunks(pixel_count) {
+        inputs.push(image.iter().map(|pixel| pixel / 255.0).collect());
+    }
+    let mut classes = Vec::new();
+    for label in labels.values.iter() {
+        if *label < 0.0 || *label > 9.0 {
+            return Err(invalid(format!("The label {} in {} isn't a digit", label, labels_path.display())))
+        }
+        classes.push(*label as usize);
+    }
+    Ok(Dataset::new(inputs, dataset::one_hot_labels(&classes, 10)))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_bytes(name: &str, bytes: &[u8]) -> io::Result<IdxArray> {
+    //Reads the bytes from a temporary file. The process id keeps runs of the tests at the same time from sharing files.
+        let path = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        fs::write(&path, bytes)?;
+        let result = read_idx(&path);
+        fs::remove_file(&path)?;
+        result
+    }
+
+    #[test]
+    fn reads_values_in_order() {
+        let array = read_bytes("simple_nn_idx_values", &[0, 0, 0x08, 2, 0, 0, 0, 2, 0, 0, 0, 3, 1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(array.dimensions, vec![2, 3]);
+        assert_eq!(array.item_size(), 3);
+        assert_eq!(array.values, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn dimensions_that_overflow_are_errors() {
+        let mut bytes = vec![0, 0, 0x0E, 4];
+        for _ in 0..4 {
+            bytes.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        }
+        assert!(read_bytes("simple_nn_idx_overflow", &bytes).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//Positions for drawing a network, worked out from the sizes of its layers instead of typed in by hand.
+//Coordinates are (x, y) with y pointing up, like in nannou. Renderers with y pointing down have to flip them.
+
+#[derive(Clone, Debug)]
+pub struct NetworkLayout {
+//The middle of every node, in evenly spaced columns. columns[0] is the inputs and columns[num + 1] is the values of layer num.
+    pub columns: Vec<Vec<(f32, f32)>>,
+    pub node_radius: f32,
+}
+
+impl NetworkLayout {
+    pub fn new(sizes: &[usize], left: f32, bottom: f32, width: f32, height: f32) -> NetworkLayout {
+    //Spreads the columns evenly across the width, and the nodes of each column evenly across the height, centered vertically.
+    //sizes[0] is the number of inputs and sizes[num + 1] is the number of values of layer num.
+        let column_count = sizes.len();
+        let largest = sizes.iter().cloned().max().unwrap_or(1).max(1);
+        let column_spacing = width / column_count.max(1) as f32;
+        let row_spacing = height / largest as f32;
+        let node_radius = (row_spacing * 0.4).min(column_spacing * 0.15).min(12.0);
+        let mut columns = Vec::new();
+        for (column_num, size) in sizes.iter().enumerate() {
+            let x = left + column_spacing * (column_num as f32 + 0.5);
+            let column_height = row_spacing * (*size as f32 - 1.0).max(0.0);
+            let mut column = Vec::new();
+            for row_num in 0..*size {
+                column.push((x, bottom + height / 2.0 + column_height / 2.0 - row_spacing * row_num as f32));
+            }
+            columns.push(column);
+        }
+        NetworkLayout {
+            columns,
+            node_radius,
+        }
+    }
+}
+
+fn horizontal_segment(x: f32, y: f32, length: f32, thickness: f32) -> Vec<(f32, f32)> {
+//A six-sided segment lying down, with its middle at (x, y).
+    let half = length / 2.0;
+    let edge = thickness / 2.0;
+    vec![(x - half, y), (x - half + edge, y + edge), (x + half - edge, y + edge),
+         (x + half, y), (x + half - edge, y - edge), (x - half + edge, y - edge)]
+}
+
+fn vertical_segment(x: f32, y: f32, length: f32, thickness: f32) -> Vec<(f32, f32)> {
+//A six-sided segment standing up, with its middle at (x, y).
+    let half = length / 2.0;
+    let edge = thickness / 2.0;
+    vec![(x, y + half), (x + edge, y + half - edge), (x + edge, y - half + edge),
+         (x, y - half), (x - edge, y - half + edge), (x - edge, y + half - edge)]
+}
+
+pub fn seven_segment_display(x: f32, y: f32, height: f32) -> Vec<Vec<(f32, f32)>> {
+//The polygons of the "18" display with its middle at (x, y), in the order of the outputs: [B, C,     A, B, C, D, E, F, G]
+//The first B and C are the "1", the rest are the "8".
+    let width = height * 0.55;
+    let thickness = height * 0.09;
+    let gap = thickness * 0.6; //Space between segments that meet.
+    let eight_x = x + width * 0.45;
+    let one_x = eight_x - width * 1.35;
+    let horizontal = width - gap;
+    let vertical = height / 2.0 - gap;
+    vec![
+        vertical_segment(one_x + width / 2.0, y + height / 4.0, vertical, thickness), // B of the "1"
+        vertical_segment(one_x + width / 2.0, y - height / 4.0, vertical, thickness), // C of the "1"
+        horizontal_segment(eight_x, y + height / 2.0, horizontal, thickness), // A
+        vertical_segment(eight_x + width / 2.0, y + height / 4.0, vertical, thickness), // B
+        vertical_segment(eight_x + width / 2.0, y - height / 4.0, vertical, thickness), // C
+        horizontal_segment(eight_x, y - height / 2.0, horizontal, thickness), // D
+        vertical_segment(eight_x - width / 2.0, y - height / 4.0, vertical, thickness), // E
+        vertical_segment(eight_x - width / 2.0, y + height / 4.0, vertical, thickness), // F
+        horizontal_segment(eight_x, y, horizontal, thickness), // G
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_spaced_and_centered() {
+        let layout = NetworkLayout::new(&[2, 4, 1], 10.0, 20.0, 300.0, 400.0);
+        assert_eq!(layout.columns.len(), 3);
+        assert_eq!(layout.columns[0], vec![(60.0, 270.0), (60.0, 170.0)]);
+        assert_eq!(layout.columns[1], vec![(160.0, 370.0), (160.0, 270.0), (160.0, 170.0), (160.0, 70.0)]);
+        assert_eq!(layout.columns[2], vec![(260.0, 220.0)]);
+        assert_eq!(layout.node_radius, 12.0);
+    }
+
+    #[test]
+    fn radius_shrinks_to_fit() {
+        let layout = NetworkLayout::new(&[10, 10], 0.0, 0.0, 100.0, 100.0);
+        assert_eq!(layout.node_radius, 4.0); // 0.4 of the 10 between rows, which is less than 0.15 of the 50 between columns
+        let layout = NetworkLayout::new(&[2, 2, 2, 2, 2], 0.0, 0.0, 100.0, 400.0);
+        assert_eq!(layout.node_radius, 3.0);
+    }
+
+    #[test]
+    fn empty_columns_leave_room() {
+        let layout = NetworkLayout::new(&[3, 0, 2], 0.0, 0.0, 300.0, 300.0);
+        assert!(layout.columns[1].is_empty());
+        assert_eq!(layout.columns[0], vec![(50.0, 250.0), (50.0, 150.0), (50.0, 50.0)]);
+        assert_eq!(layout.columns[2], vec![(250.0, 200.0), (250.0, 100.0)]);
+
+        for sizes in [vec![0, 0], vec![]].iter() { // Nothing to space out, which must not give infinite or NaN sizes
+            let layout = NetworkLayout::new(sizes, 0.0, 0.0, 300.0, 300.0);
+            assert_eq!(layout.columns.len(), sizes.len());
+            assert!(layout.columns.iter().all(|column| column.is_empty()));
+            assert!(layout.node_radius.is_finite() && layout.node_radius > 0.0);
+        }
+    }
+}
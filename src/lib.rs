@@ -0,0 +1,47 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+//The loops over nodes and weights index several lists with the same number, like the sums in the notes, so they are kept as index loops.
+#![allow(clippy::needless_range_loop)]
+//Without the std feature only the inference core is built: nodes_layers, modules, rng, static_network, quantize and session, using alloc for Vec and Box,
+//and libm for the float functions. e.g. cargo build --no-default-features --features libm
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate rand;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("simple_nn needs the std feature or the libm feature for exp and tanh");
+
+mod float;
+pub mod nodes_layers;
+pub mod modules;
+pub mod rng;
+pub mod static_network;
+pub mod quantize;
+pub mod session;
+#[cfg(feature = "std")]
+pub mod conv;
+#[cfg(feature = "std")]
+pub mod recurrent;
+#[cfg(feature = "std")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub mod layout;
+#[cfg(feature = "std")]
+pub mod gif;
+#[cfg(feature = "std")]
+pub mod svg;
+#[cfg(feature = "std")]
+pub mod idx;
+#[cfg(feature = "std")]
+pub mod preprocess;
+#[cfg(feature = "std")]
+pub mod spreadsheet;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod onnx;
+#[cfg(feature = "std")]
+pub mod codegen;
@@ -1,361 +1,891 @@
-extern crate rand;
-
 use nannou::prelude::*;
-use std::thread::sleep;
+use std::path::PathBuf;
 use std::time;
-use rand::Rng;
+
+use simple_nn::checkpoint::Checkpoint;
+use simple_nn::dataset::{self, Dataset};
+use simple_nn::gif::save_gif;
+use simple_nn::layout::{seven_segment_display, NetworkLayout};
+use simple_nn::nodes_layers::Network;
+use simple_nn::rng::SimpleRng;
+use simple_nn::session::InferenceSession;
+use simple_nn::trace::TraceFormat;
 
 
 fn main() {
     nannou::app(model)
         .update(update)
+        .exit(exit)
         .run()
 }
 
 
-struct Model {
-    training_data_in: [[f32; 4]; 16],
-    training_data_out: [[f32; 9]; 16],
-    time: usize,
-    relevant_data: usize,
-    learning_rate: f32,
-    layers: Vec<Layer>,
-    _window: WindowId,
-}
-
-#[derive(Clone)]
-struct Node {
-// A node/neuron's bias and the weights of its connections to the previous layer.
-    bias: f32,
-    weights: Vec<f32>,
-    bias_adjust: Option<f32>,
-    weight_adjusts: Vec<f32>,
+struct Settings {
+//Options given on the command line, e.g. cargo run -- --checkpoint-every 100 --resume checkpoints/checkpoint_1600.txt
+    resume: Option<PathBuf>, //--resume <checkpoint>
+    checkpoint_dir: PathBuf, //--checkpoint-dir <directory>
+    checkpoint_every: Option<usize>, //--checkpoint-every <epochs>
+    checkpoint_minutes: Option<f32>, //--checkpoint-minutes <minutes>
+    seed: Option<u64>, //--seed <number>, for a run that is the same every time, e.g. when recording the animation for the docs.
+    capture_every: Option<usize>, //--capture-every <frames>, saves every Nth frame as a numbered PNG.
+    capture_dir: PathBuf, //--capture-dir <directory>
+    capture_frames: Option<usize>, //--capture-frames <count>, quits after saving that many frames.
+    gif: Option<PathBuf>, //--gif <file>, puts the saved frames together into an animated GIF when the app quits.
+    dataset: String, //--dataset <name>, one of simple_nn::dataset::GENERATOR_NAMES, e.g. xor or spirals.
+    samples: usize, //--samples <count>, the number of samples made by the generators that aren't truth tables.
 }
 
-impl Node {
-    pub fn new(number_of_weights: usize) -> Node {
-    //Generates a new node with a random bias and random weights.
-        let mut rng = rand::thread_rng();
-
-        let mut init_weights = vec![0.0; number_of_weights];
-
-        for n in 0..number_of_weights {
-            let x: f32 = rng.gen();  // Random number in the interval [0; 1[
-            init_weights[n] = 2.0 * x  - 1.0;  // The initial weights will be in [-1; 1[
+impl Settings {
+    fn from_args() -> Settings {
+        let mut settings = Settings {
+            resume: None,
+            checkpoint_dir: PathBuf::from("checkpoints"),
+            checkpoint_every: None,
+            checkpoint_minutes: None,
+            seed: None,
+            capture_every: None,
+            capture_dir: PathBuf::from("capture"),
+            capture_frames: None,
+            gif: None,
+            dataset: "seven_segment".to_string(),
+            samples: 200,
+        };
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let mut num = 0;
+        while num < args.len() {
+            let value = match args.get(num + 1) {
+                Some(value) => value.clone(),
+                None => panic!("The option {} needs a value", args[num]),
+            };
+            match args[num].as_str() {
+                "--resume" => settings.resume = Some(PathBuf::from(value)),
+                "--checkpoint-dir" => settings.checkpoint_dir = PathBuf::from(value),
+                "--checkpoint-every" => settings.checkpoint_every = Some(value.parse().expect("--checkpoint-every needs a whole number of epochs")),
+                "--checkpoint-minutes" => settings.checkpoint_minutes = Some(value.parse().expect("--checkpoint-minutes needs a number of minutes")),
+                "--seed" => settings.seed = Some(value.parse().expect("--seed needs a whole number")),
+                "--capture-every" => settings.capture_every = Some(value.parse().expect("--capture-every needs a whole number of frames")),
+                "--capture-dir" => settings.capture_dir = PathBuf::from(value),
+                "--capture-frames" => settings.capture_frames = Some(value.parse().expect("--capture-frames needs a whole number of frames")),
+                "--gif" => settings.gif = Some(PathBuf::from(value)),
+                "--dataset" => settings.dataset = value,
+                "--samples" => settings.samples = value.parse().expect("--samples needs a whole number"),
+                other => panic!("Unknown option {}", other),
+            }
+            num += 2;
         }
-        let x: f32 = rng.gen();  // Random number in the interval [0; 1[
-        let bias = 2.0 * x  - 1.0;  // The initial weights will be in [-1; 1[
-
-        Node {
-            bias: bias,
-            weights: init_weights, // vec![0.0; number_of_weights],
-            bias_adjust: None,
-            weight_adjusts: Vec::new()
+        if settings.checkpoint_every == Some(0) {
+            panic!("--checkpoint-every needs to be at least 1");
         }
-    }
-
-    pub fn calculate(&self, previous_layer_values:&Vec<f32>) -> f32 {
-    //Calculates the value of the node based on the values of the previous layer and the node's bias and weights.
-        let mut value = self.bias;
-        let previous_layer_len = previous_layer_values.len();
-        if self.weights.len() != previous_layer_len {
-            panic!("The number of weights ({}) doesn't match the number of values ({})", self.weights.len(), previous_layer_len);
+        if settings.capture_every == Some(0) {
+            panic!("--capture-every needs to be at least 1");
         }
-        for pos_num in 0..previous_layer_len {
-            value += previous_layer_values[pos_num] * self.weights[pos_num];
+        if settings.samples == 0 {
+            panic!("--samples needs to be at least 1");
         }
-        let norm_value = 1.0 / (1.0 + (-value).exp());
-        if norm_value < 0.0 || norm_value > 1.0 {
-            panic!{"Math is broken, the sigmoid functions returns value outside [0; 1]"}
+        if (settings.gif.is_some() || settings.capture_frames.is_some()) && settings.capture_every.is_none() {
+            settings.capture_every = Some(1);
         }
-        norm_value
+        settings
     }
+}
 
-    pub fn find_adjusts(&mut self, previous_layer_values:&Vec<f32>, personal_value:f32, desired_value:f32, next_layer: &Layer) {
-    //Finds out how the node's bias and weights should be adjusted based on either a known desired value or how the next layer is set to be adjusted.
-    //If it receives a next_layer with any nodes in it, it uses the bias_adjusts of the nodes in that layer that connect with it along with its personal_value to find its own bias_adjust.
-    //Otherwise, it finds its bias_adjust from its personal_value and a specified desired_value.
-    //It then finds its weight_adjusts from its bias_adjust and previous_layer_values.
-        if self.bias_adjust.is_some() {
-            panic!("The bias adjust contained a value before adjusts were found");
-        }
-        if self.weight_adjusts.len() != 0 {
-            panic!("The weight adjusts contained {} value(s) before adjusts were found", self.weight_adjusts.len());
-        }
-        let previous_layer_len = previous_layer_values.len();
-        if self.weights.len() != previous_layer_len {
-            panic!("The number of weights ({}) doesn't match the number of values ({})", self.weights.len(), previous_layer_len);
-        }
-        let mut delta = 0.0;
-        if next_layer.node_count == 0 {
-            delta = (personal_value - desired_value) * personal_value * (1.0 - personal_value)
-        } else {
-            for num in 0..next_layer.node_count {
-                delta += next_layer.nodes[num].bias_adjust.unwrap() * next_layer.nodes[num].weights[desired_value as usize]; //If there is a next_layer, desired_value tracks which connections in it lead to the node.
-            }
-            delta *= personal_value * (1.0 - personal_value);
-        }
-        self.bias_adjust = Some(delta);
-        for pos_num in 0..previous_layer_len {
-            self.weight_adjusts.push(delta * previous_layer_values[pos_num]);
+
+struct History {
+//The cost and accuracy after every epoch, kept for the whole run so the plot can show all of it.
+    training_costs: Vec<f32>, //The average cost of the samples during the epoch, found just before each was trained on.
+    validation_costs: Vec<f32>, //The average cost of all the validation samples at the end of the epoch.
+    accuracies: Vec<f32>, //The part of the validation samples where every output is on the right side of 0.5.
+    epoch_cost: f32,
+    epoch_samples: usize,
+    log_scale: bool, //Toggled with L
+    show_accuracy: bool, //Toggled with A
+}
+
+impl History {
+    fn new() -> History {
+        History {
+            training_costs: Vec::new(),
+            validation_costs: Vec::new(),
+            accuracies: Vec::new(),
+            epoch_cost: 0.0,
+            epoch_samples: 0,
+            log_scale: false,
+            show_accuracy: true,
         }
     }
+}
 
-    // fn find_weight_adjust(previous_node:f32, personal_value:f32, desired_value:f32) -> f32 {
-    //     (personal_value - desired_value) * personal_value * (1.0 + personal_value) * previous_node
-    // }
 
-    pub fn adjust(&mut self, learning_rate: f32) {
-    //Changes the weights and biases after all nodes have found how they're supposed to be adjusted.
-        if self.bias_adjust.is_none() {
-            panic!{"Bias adjust is missing"}
-        }
-        let weight_count = self.weights.len();
-        if self.weight_adjusts.len() != weight_count {
-            panic!("The number of weight adjusts ({}) doesn't match the number of weights ({})", self.weight_adjusts.len(), weight_count);
-        }
-        self.bias -= self.bias_adjust.unwrap() * learning_rate;
-        self.bias_adjust = None;
-        for num in 0..weight_count {
-            self.weights[num] -= self.weight_adjusts[num] * learning_rate;
+struct Controls {
+//What the keyboard and mouse have changed about how the training runs and what is shown.
+    paused: bool, //Toggled with Space
+    speed: f32, //Training steps per frame. Up doubles it and Down halves it. Below 1, a step is only taken every few frames.
+    step_budget: f32, //The part of a step that has been saved up from earlier frames.
+    manual_input: Option<Vec<f32>>, //Set by clicking the input nodes. The network's prediction for it is shown instead of the training data.
+    show_grid: bool, //Show the predictions for all the numbers instead of the network. Toggled with G.
+    show_heatmap: bool, //Show the outputs over the whole input plane next to the network, for networks with 2 inputs. Toggled with H.
+    trace_next: bool, //Print every number of the next training step. Set with T, which also takes the step.
+}
+
+impl Controls {
+    fn new() -> Controls {
+        Controls {
+            paused: false,
+            speed: 1.0,
+            step_budget: 0.0,
+            manual_input: None,
+            show_grid: false,
+            show_heatmap: true,
+            trace_next: false,
         }
-        self.weight_adjusts = Vec::new();
     }
 }
 
-#[derive(Clone)]
-struct Layer {
-//A layer of nodes, their biases, and the weights of their connections to the previous layer.
-    nodes: Vec<Node>,
-    node_count: usize, //Should be equal to nodes.len() and shouldn't change.
+
+struct Model {
+    data: Dataset,
+    validation_data: Dataset, //Made by the same generator with another seed, so the validation cost shows how well the network does on samples it hasn't trained on.
+    data_seed: u64, //The seed the data was generated with, saved in checkpoints so a resumed run gets the same samples.
+    time: usize,
+    relevant_data: usize,
+    network: Network,
+    settings: Settings,
+    last_checkpoint: time::Instant,
+    show_values: bool, //Show the value, bias and delta of the node under the mouse. Toggled with V.
+    history: History,
+    controls: Controls,
+    shown: ShownValues,
+    frame_count: usize, //The number of frames so far, used to decide which frames are captured.
+    captured: Vec<PathBuf>, //The frames saved so far.
+    _window: WindowId,
+}
+
+struct ShownValues {
+//The values and deltas of every node for the shown sample.
+//Finding the deltas clones the network, so they are only found again when a training step or a click changes them, not every frame.
+    key: Option<(String, usize, Option<Vec<f32>>)>, //The dataset, time and chosen input they were found for. None when they have to be found again.
+    values: Vec<Vec<f32>>,
+    deltas: Vec<Vec<f32>>,
 }
 
-impl Layer {
-    pub fn new(previous_layer_nodes: usize, number_of_nodes: usize) -> Layer {
-    //Generates a layer of nodes, each with a random bias and a number of random weights equal to the number of nodes in the previous layer.
-        let mut nodes = Vec::new();
-        for _ in 0..number_of_nodes {
-            nodes.push(Node::new(previous_layer_nodes));
-        }
-        let node_count = nodes.len();
-        Layer {
-            nodes: nodes,
-            node_count: node_count,
+impl ShownValues {
+    fn new() -> ShownValues {
+        ShownValues {
+            key: None,
+            values: Vec::new(),
+            deltas: Vec::new(),
         }
     }
+}
 
-    pub fn calculate(&self, previous_layer_values:&Vec<f32>) -> Vec<f32> {
-    //Calculates the values of the nodes based on the values of the previous layer and the nodes' weights and biases.
-        let mut values = Vec::new();
-        for node_num in 0..self.node_count {
-            values.push(self.nodes[node_num].calculate(previous_layer_values));
-        }
-        values
-    }
-
-    pub fn find_adjusts(&mut self, previous_layer_values:&Vec<f32>, values:&Vec<f32>, desired_values:&Vec<f32>, next_layer: &Layer) {
-    //Finds out how the nodes' weights and biases should be adjusted, based either on a list of desired values, or how the next layer is set to be adjusted.
-    //The function does this by calling find_adjusts for each node.
-        for node_num in 0..self.node_count {
-            self.nodes[node_num].find_adjusts(
-                                              previous_layer_values,
-                                              values[node_num], //personal_value, is used to track the calculated value of the relevant node.
-                                              if next_layer.node_count > 0 {node_num as f32} //If the next layer contains anything, desired_value is used to track the position of the relevant node.
-                                                else {desired_values[node_num]}, //Otherwise, desired_value is used to track the desired value.
-                                              next_layer
-                                              );
-        }
+fn refresh_shown(model: &mut Model) {
+//Finds the values and deltas of the shown sample, if they have changed since they were last found.
+    let key = Some((model.settings.dataset.clone(), model.time, model.controls.manual_input.clone()));
+    if model.shown.key == key {
+        return
     }
+    let (inputs, desired_outputs) = shown_sample(model);
+    model.shown.values = model.network.calculate(&inputs);
+    model.shown.deltas = model.network.find_deltas(&inputs, &desired_outputs);
+    model.shown.key = key;
+}
 
-    pub fn adjust(&mut self, learning_rate:f32) {
-    //Calls the adjust function of every node in the layer, which does the following for the node:
-    //Changes the weights and biases after all nodes have found how they're supposed to be adjusted.
-        for node_num in 0..self.node_count {
-            self.nodes[node_num].adjust(learning_rate)
-        }
+fn shown_sample(model: &Model) -> (Vec<f32>, Vec<f32>) {
+//The inputs that are shown, along with their desired outputs.
+//Those are the active training data, unless an input has been chosen by clicking the input nodes.
+    match &model.controls.manual_input {
+        Some(inputs) => {
+            for num in 0..model.data.len() {
+                if model.data.inputs[num] == *inputs {
+                    return (inputs.clone(), model.data.outputs[num].clone())
+                }
+            }
+            (inputs.clone(), vec![0.0; model.data.outputs[0].len()])
+        },
+        None => (model.data.inputs[model.relevant_data].clone(), model.data.outputs[model.relevant_data].clone()),
     }
 }
 
-// fn calculate(inputs: &Vec<f32>, layers:&Vec<Layer>) -> Vec<Vec<f32>> {
-fn calculate(model: &Model) -> Vec<Vec<f32>> {
-//Calculates the values of all nodes based on the active training data and the weights and biases.
+fn calculate(model: &Model) -> &[Vec<f32>] {
+//The values of all nodes for the shown inputs, as they were found after the last training step or click.
 //The outer vector of the output is the layer, the inner vector is the position in the layer. To get the output layer from values, say values[values.len() - 1]
-    let mut values = vec![model.layers[0].calculate(&model.training_data_in[model.relevant_data].to_vec())];
-    for num in 1..model.layers.len() {
-        values.push(model.layers[num].calculate(&values[num-1]));
-    }
-    values
+    &model.shown.values
 }
 
 fn find_cost(model: &Model) -> f32 {
 //Finds the cost function of the active training data, which is the difference between the current result and the desired result.
 //Not actually used for anything, since the find_adjust use calculations that have already taken the cost function into accout.
-    let values = calculate(model);
+    model.network.find_cost(&model.data.inputs[model.relevant_data], &model.data.outputs[model.relevant_data])
+}
+
+fn find_make_adjust(model: &mut Model) {
+//Finds out how the weights and biases should be adjusted for the active training data, and adjusts them.
+//After T is pressed, every number of the step is printed as well.
+    let inputs = model.data.inputs[model.relevant_data].clone();
+    let desired_outputs = model.data.outputs[model.relevant_data].clone();
+    if model.controls.trace_next {
+        model.controls.trace_next = false;
+        match model.network.trace_make_adjust(&inputs, &desired_outputs) {
+            Ok(trace) => print!("{}", trace.render(TraceFormat::Text)),
+            Err(error) => { // Nothing has been changed yet, so the step is taken without the trace
+                println!("Couldn't trace the step: {}", error);
+                model.network.find_make_adjust(&inputs, &desired_outputs);
+            },
+        }
+    } else {
+        model.network.find_make_adjust(&inputs, &desired_outputs);
+    }
+}
+
+fn validate(model: &Model) -> (f32, f32) {
+//Finds the average cost and the accuracy over the validation samples.
+//The truth tables, like the 7-segment display, have no other samples, so for them these are the training samples again.
+    let data = &model.validation_data;
     let mut cost = 0.0;
-    for (num, value) in values[values.len() - 1].iter().enumerate() {
-        cost += (value - model.training_data_out[model.relevant_data][num]).powi(2)/2.0;
+    let mut correct = 0;
+    for num in 0..data.len() {
+        let inputs = &data.inputs[num];
+        let desired_outputs = &data.outputs[num];
+        cost += model.network.find_cost(inputs, desired_outputs);
+        let values = model.network.calculate(inputs);
+        let outputs = &values[values.len() - 1];
+        if !is_wrong(outputs, desired_outputs) {
+            correct += 1;
+        }
     }
-    cost
+    let count = data.len() as f32;
+    (cost / count, correct as f32 / count)
 }
 
-// fn find_make_adjust(inputs: &Vec<f32>, layers:&mut Vec<Layer>, desired_outputs:&Vec<f32>, learning_rate:f32) {
-fn find_make_adjust(model: &mut Model) {
-//Finds out how the weights and biases should be adjusted, based on the difference between the results of the calculate function and the desired outputs.
-//It does it in reverse order, because that's how you have to do it.
-//Then it implements all of the changes after they have all been calculated. That doesn't have to be done in reverse order, so it isn't.
-    let values = calculate(&model);
-    let layer_count = model.layers.len();
-    model.layers[layer_count-1].find_adjusts(&values[layer_count-2], &values[layer_count-1], &model.training_data_out[model.relevant_data].to_vec(), &Layer::new(0, 0));
-    for num in (1..layer_count-1).rev() {
-        let next_layer = model.layers[num+1].clone();
-        model.layers[num].find_adjusts(&values[num-1], &values[num], &Vec::new(), &next_layer);
+fn save_checkpoint(model: &mut Model) {
+//Saves everything needed to continue training later with --resume.
+    let checkpoint = Checkpoint {
+        network: model.network.clone(),
+        time: model.time,
+        relevant_data: model.relevant_data,
+        dataset: model.settings.dataset.clone(),
+        data_seed: model.data_seed,
+        samples: model.settings.samples,
+    };
+    let path = model.settings.checkpoint_dir.join(format!("checkpoint_{}.txt", model.time));
+    let result = std::fs::create_dir_all(&model.settings.checkpoint_dir).and_then(|_| checkpoint.save(&path));
+    match result {
+        Ok(()) => println!("Saved checkpoint {}", path.display()),
+        Err(error) => println!("Couldn't save checkpoint {}: {}", path.display(), error),
+    }
+    model.last_checkpoint = time::Instant::now();
+}
+
+fn training_data(settings: &Settings, name: &str, seed: u64) -> Dataset {
+//The samples the network is trained or validated on, made by one of the generators in simple_nn::dataset.
+    match dataset::generate(name, settings.samples, &mut SimpleRng::new(seed)) {
+        Some(data) => data,
+        None => panic!("Unknown dataset {}, use one of {}", name, dataset::GENERATOR_NAMES.join(", ")),
     }
-    let next_layer = model.layers[1].clone();
-    model.layers[0].find_adjusts(&model.training_data_in[model.relevant_data].to_vec(), &values[0], &Vec::new(), &next_layer);
-    for num in 0..layer_count {
-        model.layers[num].adjust(model.learning_rate);
+}
+
+fn validation_seed(data_seed: u64) -> u64 {
+//The seed of the validation samples, found from the seed of the training samples so a resumed run gets the same ones.
+    data_seed.wrapping_add(1)
+}
+
+fn new_network(settings: &Settings, data: &Dataset) -> Network {
+//A network with two hidden layers of 8 nodes, and as many inputs and outputs as the data has.
+    let learning_rate = 0.5;
+    let node_nums = vec![data.inputs[0].len(), 8, 8, data.outputs[0].len()];
+    match settings.seed {
+        Some(seed) => Network::with_seed(node_nums, learning_rate, seed),
+        None => Network::new(node_nums, learning_rate),
     }
 }
 
+fn switch_dataset(model: &mut Model) {
+//Starts over with a new network on the next dataset. Pressing D goes through all of them.
+    let position = dataset::GENERATOR_NAMES.iter().position(|name| *name == model.settings.dataset).unwrap_or(0);
+    model.settings.dataset = dataset::GENERATOR_NAMES[(position + 1) % dataset::GENERATOR_NAMES.len()].to_string();
+    model.data = training_data(&model.settings, &model.settings.dataset, model.data_seed);
+    model.validation_data = training_data(&model.settings, &model.settings.dataset, validation_seed(model.data_seed));
+    model.network = new_network(&model.settings, &model.data);
+    model.time = 0;
+    model.relevant_data = 0;
+    model.history = History::new();
+    model.controls.manual_input = None;
+    model.controls.show_grid = false;
+    model.shown = ShownValues::new(); // The time and dataset name can be the same as before, but the network isn't
+    println!("Training on {} with {} samples", model.settings.dataset, model.data.len());
+}
+
 fn model(app: &App) -> Model {
     let _window = app
     .new_window()
     .with_dimensions(700, 700)
     .with_title("Simple Neural Network")
     .view(view)
+    .key_pressed(key_pressed)
+    .mouse_pressed(mouse_pressed)
     .build()
     .unwrap();
 
-    // The numbers 0-15 in binary
-    let training_data_in: [[f32; 4]; 16] = [
-        [0.0, 0.0, 0.0, 0.0],
-        [0.0, 0.0, 0.0, 1.0],
-        [0.0, 0.0, 1.0, 0.0],
-        [0.0, 0.0, 1.0, 1.0],
-        [0.0, 1.0, 0.0, 0.0],
-        [0.0, 1.0, 0.0, 1.0],
-        [0.0, 1.0, 1.0, 0.0],
-        [0.0, 1.0, 1.0, 1.0],
-        [1.0, 0.0, 0.0, 0.0],
-        [1.0, 0.0, 0.0, 1.0],
-        [1.0, 0.0, 1.0, 0.0],
-        [1.0, 0.0, 1.0, 1.0],
-        [1.0, 1.0, 0.0, 0.0],
-        [1.0, 1.0, 0.0, 1.0],
-        [1.0, 1.0, 1.0, 0.0],
-        [1.0, 1.0, 1.0, 1.0],
-    ] ;
-
-    // The numbers 0-15 as 7-segment display: "[1   8]" [B, C,     A, B, C, D, E, F, G]
-    let training_data_out: [[f32; 9]; 16] = [
-        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0], // 0
-        [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
-        [0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
-        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0],
-        [0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0],
-        [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0], // 5
-        [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0],
-        [0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
-        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],
-        [0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0],
-        [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0], //10
-        [1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0],
-        [1.0, 1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0],
-        [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0],
-        [1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 1.0],
-        [1.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0], // 15
-    ] ;
-
-    let time = 0;
-
-    let relevant_data = 1;
+    let mut time = 0;
 
-    let learning_rate = 0.5;
+    let mut relevant_data = 0;
+
+    let mut settings = Settings::from_args();
+
+    let mut data_seed = settings.seed.unwrap_or_else(rand::random);
+
+    let checkpoint = match &settings.resume {
+        Some(path) => match Checkpoint::load(path) {
+            Ok(checkpoint) => Some(checkpoint),
+            Err(error) => panic!("Couldn't load the checkpoint {}: {}", path.display(), error),
+        },
+        None => None,
+    };
+
+    if let Some(checkpoint) = &checkpoint { // The data has to be the same as before for the run to continue where it stopped
+        settings.dataset = checkpoint.dataset.clone();
+        settings.samples = checkpoint.samples;
+        data_seed = checkpoint.data_seed;
+    }
+
+    let data = training_data(&settings, &settings.dataset, data_seed);
+
+    let validation_data = training_data(&settings, &settings.dataset, validation_seed(data_seed));
+
+    let mut network = new_network(&settings, &data);
+
+    if let Some(checkpoint) = checkpoint {
+        if checkpoint.relevant_data >= data.len() {
+            panic!("The checkpoint continues at sample {}, but {} only has {} samples", checkpoint.relevant_data, settings.dataset, data.len());
+        }
+        network = checkpoint.network;
+        time = checkpoint.time;
+        relevant_data = checkpoint.relevant_data;
+        println!("Resuming on {} at time {}", settings.dataset, time);
+    }
+
+    let last_checkpoint = time::Instant::now();
+
+    let show_values = true;
 
-    let mut layers = Vec::new();
-    layers.push(Layer::new(4, 8));
-    layers.push(Layer::new(8, 8));
-    layers.push(Layer::new(8, 9));
+    let history = History::new();
+
+    let controls = Controls::new();
+
+    if settings.capture_every.is_some() {
+        std::fs::create_dir_all(&settings.capture_dir).unwrap();
+    }
 
-    Model {
-        training_data_in,
-        training_data_out,
+    let mut model = Model {
+        data,
+        validation_data,
+        data_seed,
         time,
         relevant_data,
-        learning_rate,
-        layers,
-        _window }
+        network,
+        settings,
+        last_checkpoint,
+        show_values,
+        history,
+        controls,
+        shown: ShownValues::new(),
+        frame_count: 0,
+        captured: Vec::new(),
+        _window };
+    refresh_shown(&mut model);
+    model
+}
+
+
+fn update(app: &App, model: &mut Model, _update: Update) {
+
+    if let Some(frames) = model.settings.capture_frames {
+        if model.captured.len() >= frames {
+            app.quit();
+            return
+        }
+    }
+    if let Some(every) = model.settings.capture_every { // Decides whether the frame drawn after this update is saved
+        if model.frame_count % every == 0 {
+            let path = model.settings.capture_dir.join(format!("frame_{:05}.png", model.captured.len()));
+            app.main_window().capture_frame(&path);
+            model.captured.push(path);
+        }
+    }
+    model.frame_count += 1;
+
+    if model.controls.paused {
+        return
+    }
+    model.controls.step_budget += model.controls.speed;
+    while model.controls.step_budget >= 1.0 {
+        train_step(model);
+        model.controls.step_budget -= 1.0;
+    }
+    refresh_shown(model);
 }
 
 
-fn update(_app: &App, model: &mut Model, _update: Update) {
+fn exit(_app: &App, model: Model) {
+//Puts the captured frames together into a GIF, if one was asked for with --gif.
+    let path = match &model.settings.gif {
+        Some(path) => path.clone(),
+        None => return,
+    };
+    let mut frames = Vec::new();
+    let mut size = (0, 0);
+    for frame_path in model.captured.iter() {
+        let image = match nannou::image::open(frame_path) {
+            Ok(image) => image.to_rgba(),
+            Err(error) => {
+                println!("Skipping {}: {}", frame_path.display(), error);
+                continue
+            },
+        };
+        if frames.is_empty() {
+            size = image.dimensions();
+        }
+        if image.dimensions() != size { // The window was resized while capturing
+            println!("Skipping {}, which isn't {}x{}", frame_path.display(), size.0, size.1);
+            continue
+        }
+        frames.push(image.into_raw());
+    }
+    let delay = (model.settings.capture_every.unwrap_or(1) * 100 / 60).max(2) as u16; // Hundredths of a second, assuming 60 frames per second
+    match save_gif(&path, size.0 as usize, size.1 as usize, &frames, delay) {
+        Ok(()) => println!("Saved {} frames to {}", frames.len(), path.display()),
+        Err(error) => println!("Couldn't save {}: {}", path.display(), error),
+    }
+}
+
 
+fn train_step(model: &mut Model) {
+//Trains on the active training data and moves on to the next sample.
+    model.history.epoch_cost += find_cost(model);
+    model.history.epoch_samples += 1;
     find_make_adjust(model);
     model.time += 1;
     model.relevant_data += 1;
 
-    if model.relevant_data > 15 {
+    if model.relevant_data >= model.data.len() {
         model.relevant_data = 0;
     }
 
-    // if model.time > 4_000 {
-    if model.time % 512 < 16 {
+    if model.relevant_data == 0 {
+        let (validation_cost, accuracy) = validate(model);
+        let history = &mut model.history;
+        history.training_costs.push(history.epoch_cost / history.epoch_samples as f32);
+        history.validation_costs.push(validation_cost);
+        history.accuracies.push(accuracy);
+        history.epoch_cost = 0.0;
+        history.epoch_samples = 0;
+    }
+
+    let epoch = model.time / model.data.len();
+    let epochs_due = match model.settings.checkpoint_every {
+        Some(epochs) => model.relevant_data == 0 && epoch % epochs == 0,
+        None => false,
+    };
+    let minutes_due = match model.settings.checkpoint_minutes {
+        Some(minutes) => model.last_checkpoint.elapsed().as_secs() as f32 >= minutes * 60.0,
+        None => false,
+    };
+    if epochs_due || minutes_due {
+        save_checkpoint(model);
+    }
+
+    if model.time % 512 == 0 {
         println!("time: {:?} cost: {:?}", model.time, find_cost(model));
-        sleep(time::Duration::new(0, 500000000)); // sec, nano sec
     }
 }
 
 
+fn key_pressed(_app: &App, model: &mut Model, key: Key) {
+    match key {
+        Key::Space => model.controls.paused = !model.controls.paused,
+        Key::S => train_step(model), // One sample
+        Key::T => { // One sample, printing everything that happens
+            model.controls.trace_next = true;
+            train_step(model);
+        },
+        Key::E => { // The rest of the epoch
+            train_step(model);
+            while model.relevant_data != 0 {
+                train_step(model);
+            }
+        },
+        Key::Up => model.controls.speed = (model.controls.speed * 2.0).min(4096.0),
+        Key::Down => model.controls.speed = (model.controls.speed / 2.0).max(1.0 / 64.0),
+        Key::Escape | Key::M => model.controls.manual_input = None, // Back to showing the training data
+        Key::V => model.show_values = !model.show_values,
+        Key::G => model.controls.show_grid = !model.controls.show_grid && shows_display(model),
+        Key::L => model.history.log_scale = !model.history.log_scale,
+        Key::A => model.history.show_accuracy = !model.history.show_accuracy,
+        Key::H => model.controls.show_heatmap = !model.controls.show_heatmap,
+        Key::D => switch_dataset(model),
+        _ => (),
+    }
+    refresh_shown(model);
+}
+
+
+fn mouse_pressed(app: &App, model: &mut Model, _button: MouseButton) {
+//Clicking an input node flips it between 0 and 1, and shows the network's prediction for the chosen input.
+//Clicking the heatmap chooses the inputs at that point instead.
+    let layout = network_layout(model, app.window_rect());
+    let mouse = pt2(app.mouse.x, app.mouse.y);
+    if shows_heatmap(model) {
+        let rect = heatmap_rect(app.window_rect());
+        if rect.contains(mouse) {
+            let (x_range, y_range) = input_ranges(&model.data);
+            let x = x_range.0 + (x_range.1 - x_range.0) * (mouse.x - rect.left()) / rect.w();
+            let y = y_range.0 + (y_range.1 - y_range.0) * (mouse.y - rect.bottom()) / rect.h();
+            model.controls.manual_input = Some(vec![x, y]);
+            refresh_shown(model);
+            return
+        }
+    }
+    let reach = layout.node_radius + 2.0;
+    for (num, position) in layout.columns[0].iter().enumerate() {
+        if (position.0 - mouse.x).powi(2) + (position.1 - mouse.y).powi(2) <= reach * reach {
+            let mut inputs = shown_sample(model).0;
+            inputs[num] = if inputs[num] > 0.5 {0.0} else {1.0};
+            model.controls.manual_input = Some(inputs);
+            refresh_shown(model);
+            return
+        }
+    }
+}
+
+
+fn draw_controls(model: &Model, draw: &nannou::app::Draw, window: Rect) {
+//Shows the state of the controls and which keys change them.
+    let state = if model.controls.paused {"paused".to_string()} else {format!("{} steps per frame", model.controls.speed)};
+    let shown = if model.controls.manual_input.is_some() {"chosen input (M: back to training data)"} else {"training data (click the inputs to choose)"};
+    let text = format!("{}   showing: {}\nSpace: pause   S: step   T: traced step   E: epoch   Up/Down: speed   V: values   G: all numbers   H: heatmap   D: next dataset   L: log scale   A: accuracy", state, shown);
+    draw.text(&text).x_y(window.x(), window.top() - 20.0).w_h(window.w() - 40.0, 30.0).font_size(11).color(rgb(0.7, 0.7, 0.7));
+}
+
+
 fn view(app: &App, model: &Model, frame: &Frame) {
 
     let draw = app.draw();
 
-    draw_results(model, &draw);
+    let window = app.window_rect();
+
+    if model.controls.show_grid {
+        draw.background().color(BLACK);
+        let plot_top = plot_rect(window).top();
+        draw_digit_grid(model, &draw, Rect::from_corners(pt2(window.left() + 20.0, plot_top + 20.0), pt2(window.right() - 20.0, window.top() - 50.0)));
+    } else {
+        let layout = network_layout(model, window);
+
+        draw_results(model, &draw, &layout, window);
+
+        if shows_heatmap(model) {
+            draw_heatmap(model, &draw, heatmap_rect(window));
+        }
+
+        if model.show_values {
+            draw_node_values(model, &draw, &layout, pt2(app.mouse.x, app.mouse.y));
+        }
+    }
+
+    draw_history(&model.history, &draw, plot_rect(window));
+
+    draw_controls(model, &draw, window);
 
     draw.to_frame(app, frame).unwrap();
 }
 
 
-fn draw_results(model: &Model, draw: &nannou::app::Draw) {
+fn point(position: (f32, f32)) -> Point2 {
+    pt2(position.0, position.1)
+}
+
+fn shows_display(model: &Model) -> bool {
+//The 7-segment display is only drawn when the network has an output for each segment.
+    let results = calculate(model);
+    results[results.len() - 1].len() == 9
+}
+
+fn shows_heatmap(model: &Model) -> bool {
+//The heatmap can only be drawn when the inputs are points in a plane.
+    model.controls.show_heatmap && model.data.inputs[0].len() == 2
+}
 
-    let tdi = model.training_data_in[model.relevant_data];
-    // let tdu = model.training_data_out[model.relevant_data];
+fn network_layout(model: &Model, window: Rect) -> NetworkLayout {
+//Places the nodes based on the size of each layer. The network fills the window, except for the right part if the 7-segment display or the heatmap is shown.
+    let results = calculate(model);
+    let mut sizes = vec![model.data.inputs[model.relevant_data].len()];
+    for values in results.iter() {
+        sizes.push(values.len());
+    }
+    let margin = 20.0;
+    let width = if shows_display(model) || shows_heatmap(model) {window.w() * 0.6} else {window.w()};
+    let bottom = plot_rect(window).top(); // The plot takes up the bottom of the window
+    NetworkLayout::new(&sizes, window.left() + margin, bottom + margin, width - 2.0 * margin, window.top() - bottom - 2.0 * margin)
+}
+
+fn plot_rect(window: Rect) -> Rect {
+//The part of the window used for the cost plot.
+    let margin = 20.0;
+    let height = window.h() * 0.25;
+    Rect::from_x_y_w_h(window.x(), window.bottom() + margin + height / 2.0, window.w() - 2.0 * margin, height)
+}
+
+fn plot_points(values: &[f32], rect: Rect, low: f32, high: f32, log_scale: bool) -> Vec<Point2> {
+//Turns a curve into points in the plot. Long curves are thinned out to about one point per pixel, so the whole history fits.
+    let step = (values.len() as f32 / rect.w()).ceil().max(1.0) as usize;
+    let scale = |value: f32| if log_scale {value.max(1e-6).log10()} else {value};
+    let (low, high) = (scale(low), scale(high));
+    let mut points = Vec::new();
+    for num in (0..values.len()).step_by(step) {
+        let x = rect.left() + rect.w() * num as f32 / (values.len() - 1).max(1) as f32;
+        let y = rect.bottom() + rect.h() * (scale(values[num]) - low) / (high - low).max(1e-6);
+        points.push(pt2(x, y));
+    }
+    points
+}
+
+fn draw_curve(draw: &nannou::app::Draw, points: &[Point2], color: Rgba) {
+    for num in 1..points.len() {
+        draw.line().start(points[num - 1]).end(points[num]).thickness(1.5).color(color);
+    }
+}
+
+fn draw_history(history: &History, draw: &nannou::app::Draw, rect: Rect) {
+//Plots the training and validation cost of every epoch so far, and the accuracy if it is turned on.
+//The costs share one scale, which is logarithmic when log_scale is on. The accuracy always goes from 0 to 1.
+    let axis_color = rgba(0.6, 0.6, 0.6, 1.0);
+    draw.line().start(rect.bottom_left()).end(rect.top_left()).thickness(1.0).color(axis_color);
+    draw.line().start(rect.bottom_left()).end(rect.bottom_right()).thickness(1.0).color(axis_color);
+
+    let training_color = rgba(1.0, 0.6, 0.1, 1.0);
+    let validation_color = rgba(0.2, 0.8, 1.0, 1.0);
+    let accuracy_color = rgba(0.3, 1.0, 0.3, 1.0);
+    let epochs = history.training_costs.len();
+    let mut legend = format!("epochs: {}   training cost (orange)   validation cost (blue)", epochs);
+    if history.show_accuracy {
+        legend.push_str("   accuracy (green)");
+    }
+    if history.log_scale {
+        legend.push_str("   log scale");
+    }
+    draw.text(&legend).x_y(rect.x(), rect.top() + 8.0).w_h(rect.w(), 16.0).font_size(12).color(WHITE);
+    if epochs == 0 {
+        return
+    }
+
+    let all_costs = history.training_costs.iter().chain(history.validation_costs.iter());
+    let high = all_costs.clone().cloned().fold(f32::MIN, f32::max);
+    let low = if history.log_scale {all_costs.cloned().fold(f32::MAX, f32::min)} else {0.0};
+    draw_curve(draw, &plot_points(&history.training_costs, rect, low, high, history.log_scale), training_color);
+    draw_curve(draw, &plot_points(&history.validation_costs, rect, low, high, history.log_scale), validation_color);
+    if history.show_accuracy {
+        draw_curve(draw, &plot_points(&history.accuracies, rect, 0.0, 1.0, false), accuracy_color);
+    }
+    draw.text(&format!("{:.4}", high)).x_y(rect.left() + 30.0, rect.top() - 8.0).w_h(60.0, 16.0).font_size(10).color(axis_color);
+    draw.text(&format!("{:.4}", low)).x_y(rect.left() + 30.0, rect.bottom() + 8.0).w_h(60.0, 16.0).font_size(10).color(axis_color);
+}
+
+fn draw_weights(model: &Model, draw: &nannou::app::Draw, layout: &NetworkLayout) {
+//Draws every weight as a line from the node in the previous layer to the node it leads to.
+//Positive weights are blue and negative weights are red. The larger the weight, the thicker and less transparent the line.
+    for (layer_num, module) in model.network.layers.iter().enumerate() {
+        let layer = match module.as_layer() {
+            Some(layer) => layer,
+            None => continue,
+        };
+        for (node_num, node) in layer.nodes.iter().enumerate() {
+            for (weight_num, weight) in node.weights.iter().enumerate() {
+                let strength = (weight.abs() / 4.0).min(1.0); // Weights above 4 are drawn as if they were 4
+                let color = if *weight > 0.0 {rgba(0.2, 0.5, 1.0, 0.15 + 0.85 * strength)} else {rgba(1.0, 0.25, 0.2, 0.15 + 0.85 * strength)};
+                draw.line()
+                    .start(point(layout.columns[layer_num][weight_num]))
+                    .end(point(layout.columns[layer_num + 1][node_num]))
+                    .thickness(0.5 + 3.5 * strength)
+                    .color(color);
+            }
+        }
+    }
+}
+
+fn draw_node_values(model: &Model, draw: &nannou::app::Draw, layout: &NetworkLayout, mouse: Point2) {
+//Writes the value of the node under the mouse next to it, along with the bias and delta if it belongs to a dense layer.
+    let reach = layout.node_radius + 2.0;
+    for column_num in 1..layout.columns.len() {
+        for (node_num, position) in layout.columns[column_num].iter().enumerate() {
+            if (position.0 - mouse.x).powi(2) + (position.1 - mouse.y).powi(2) > reach * reach {
+                continue;
+            }
+            let results = calculate(model);
+            let value = results[column_num - 1][node_num];
+            let text = match model.network.layers[column_num - 1].as_layer() {
+                Some(layer) => format!("value: {:.3}\nbias: {:.3}\ndelta: {:.4}", value, layer.nodes[node_num].bias, model.shown.deltas[column_num - 1][node_num]),
+                None => format!("value: {:.3}", value),
+            };
+            draw.rect().x_y(position.0 + 60.0, position.1 - 20.0).w_h(100.0, 50.0).color(rgba(0.0, 0.0, 0.0, 0.8));
+            draw.text(&text).x_y(position.0 + 60.0, position.1 - 20.0).w_h(100.0, 50.0).font_size(12).color(WHITE);
+            return
+        }
+    }
+}
+
+fn draw_results(model: &Model, draw: &nannou::app::Draw, layout: &NetworkLayout, window: Rect) {
+
+    let (tdi, target) = shown_sample(model);
     let results = calculate(model);
     let tdu = &results[results.len() - 1];
 
-    draw.ellipse().x_y(-300.0, 5.0).radius(10.0).color(rgb(tdi[0], tdi[0], tdi[0])); // Input
-    draw.ellipse().x_y(-300.0, 62.0).radius(10.0).color(rgb(tdi[1], tdi[1], tdi[1]));
-    draw.ellipse().x_y(-300.0, 123.0).radius(10.0).color(rgb(tdi[2], tdi[2], tdi[2]));
-    draw.ellipse().x_y(-300.0, 192.0).radius(10.0).color(rgb(tdi[3], tdi[3], tdi[3]));
-
-    draw.ellipse().x_y(-180.0, 5.0).radius(6.0).color(rgb(1.0, 1.0, 1.0)); // Layer 1
-    draw.ellipse().x_y(-180.0, 32.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 59.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 86.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 112.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 138.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 165.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-180.0, 192.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-
-    draw.ellipse().x_y(-80.0, 5.0).radius(6.0).color(rgb(1.0, 1.0, 1.0)); // Layer 2
-    draw.ellipse().x_y(-80.0, 32.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 59.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 86.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 112.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 138.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 165.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-    draw.ellipse().x_y(-80.0, 192.0).radius(6.0).color(rgb(1.0, 1.0, 1.0));
-
-    // "1" part of the 7 segment display
-    draw.polygon().color(rgb(tdu[1], tdu[1], tdu[1])).points(vec!(pt2(106.0 - 62.0, 184.0), pt2(112.0 - 62.0, 178.0),pt2(112.0 - 62.0, 112.0), pt2(106.0 - 62.0, 103.0), pt2(96.0 - 62.0, 109.0), pt2(96.0 - 62.0, 176.0))); // B
-    draw.polygon().color(rgb(tdu[0], tdu[0], tdu[0])).points(vec!(pt2(106.0 - 62.0, 184.0 - 85.0), pt2(112.0 - 62.0, 178.0 - 85.0),pt2(112.0 - 62.0, 112.0 - 85.0), pt2(106.0 - 62.0, 103.0 - 85.0), pt2(96.0 - 62.0, 109.0 - 85.0), pt2(96.0 - 62.0, 176.0 - 85.0))); // C
-
-    // "8" part of the 7 segment display
-    draw.polygon().color(rgb(tdu[2], tdu[2], tdu[2])).points(vec!(pt2(108.0, 190.0), pt2(113.0, 197.0),pt2(163.0, 197.0), pt2(172.0, 189.0), pt2(161.0, 182.0), pt2(115.0, 182.0))); // A
-    draw.polygon().color(rgb(tdu[3], tdu[3], tdu[3])).points(vec!(pt2(106.0 + 67.0, 184.0), pt2(112.0 + 67.0, 178.0),pt2(112.0 + 67.0, 112.0), pt2(106.0 + 67.0, 103.0), pt2(96.0 + 67.0, 109.0), pt2(96.0 + 67.0, 176.0))); // B
-    draw.polygon().color(rgb(tdu[4], tdu[4], tdu[4])).points(vec!(pt2(106.0 + 67.0, 184.0 - 85.0), pt2(112.0 + 67.0, 178.0 - 85.0),pt2(112.0 + 67.0, 112.0 - 85.0), pt2(106.0 + 67.0, 103.0 - 85.0), pt2(96.0 + 67.0, 109.0 - 85.0), pt2(96.0 + 67.0, 176.0 - 85.0))); // C
-    draw.polygon().color(rgb(tdu[5], tdu[5], tdu[5])).points(vec!(pt2(108.0, 190.0 - 176.0), pt2(113.0, 197.0 - 176.0),pt2(163.0, 197.0 - 176.0), pt2(172.0, 189.0 - 176.0), pt2(161.0, 182.0 - 176.0), pt2(115.0, 182.0 - 176.0))); // D
-    draw.polygon().color(rgb(tdu[6], tdu[6], tdu[6])).points(vec!(pt2(106.0, 184.0 - 85.0), pt2(112.0, 178.0 - 85.0),pt2(112.0, 112.0 - 85.0), pt2(106.0, 103.0 - 85.0), pt2(96.0, 109.0 - 85.0), pt2(96.0, 176.0 - 85.0))); // E
-    draw.polygon().color(rgb(tdu[7], tdu[7], tdu[7])).points(vec!(pt2(106.0, 184.0), pt2(112.0, 178.0),pt2(112.0, 112.0), pt2(106.0, 103.0), pt2(96.0, 109.0), pt2(96.0, 176.0))); // F
-    draw.polygon().color(rgb(tdu[8], tdu[8], tdu[8])).points(vec!(pt2(108.0, 190.0 - 90.0), pt2(113.0, 197.0 - 90.0),pt2(163.0, 197.0 - 90.0), pt2(172.0, 189.0 - 90.0), pt2(161.0, 182.0 - 90.0), pt2(115.0, 182.0 - 90.0))); // G
+    draw.background().color(BLACK);
+    draw_weights(model, draw, layout); // Drawn first, so the nodes are drawn on top of the lines
+
+    for (column_num, column) in layout.columns.iter().enumerate() { // The inputs and every layer, shaded by their values
+        let values = if column_num == 0 {&tdi} else {&results[column_num - 1]};
+        for (node_num, position) in column.iter().enumerate() {
+            let value = values[node_num].clamp(0.0, 1.0);
+            draw.ellipse().xy(point(*position)).radius(layout.node_radius + 1.0).color(rgb(0.4, 0.4, 0.4)); // Outline, so nodes with value 0 can still be seen
+            draw.ellipse().xy(point(*position)).radius(layout.node_radius).color(rgb(value, value, value));
+        }
+    }
+
+    if shows_display(model) { // The prediction next to the target, centered in the part of the window above the plot
+        let bottom = plot_rect(window).top();
+        let y = (window.top() + bottom) / 2.0;
+        let height = ((window.top() - bottom) * 0.4).min(window.w() * 0.12);
+        let label_y = y - height * 0.75;
+        draw_display(draw, window.left() + window.w() * 0.7, y, height, tdu, Some(&target));
+        draw.text("prediction").x_y(window.left() + window.w() * 0.7, label_y).w_h(100.0, 16.0).font_size(12).color(WHITE);
+        draw_display(draw, window.left() + window.w() * 0.9, y, height, &target, None);
+        draw.text("target").x_y(window.left() + window.w() * 0.9, label_y).w_h(100.0, 16.0).font_size(12).color(WHITE);
+    }
+}
+
+fn is_wrong(values: &[f32], targets: &[f32]) -> bool {
+//A digit is shown wrong if any segment is on the wrong side of 0.5.
+    (0..values.len()).any(|num| (values[num] > 0.5) != (targets[num] > 0.5))
+}
+
+fn draw_display(draw: &nannou::app::Draw, x: f32, y: f32, height: f32, values: &[f32], targets: Option<&[f32]>) {
+//Draws the 7-segment display, with each segment shaded by its value.
+//If targets are given, segments on the wrong side of 0.5 get a red border, so it is easy to see which ones the network gets wrong.
+    let polygons = seven_segment_display(x, y, height);
+    for (num, polygon) in polygons.iter().enumerate() {
+        let points: Vec<Point2> = polygon.iter().map(|position| point(*position)).collect();
+        if let Some(targets) = targets {
+            if (values[num] > 0.5) != (targets[num] > 0.5) {
+                let count = points.len() as f32;
+                let middle = pt2(points.iter().map(|p| p.x).sum::<f32>() / count, points.iter().map(|p| p.y).sum::<f32>() / count);
+                let border: Vec<Point2> = points.iter().map(|p| middle + (*p - middle) * 1.3).collect();
+                draw.polygon().color(rgb(1.0, 0.1, 0.1)).points(border);
+            }
+        }
+        let value = values[num].clamp(0.0, 1.0);
+        let shade = 0.12 + 0.88 * value; // Segments that are off are dark gray, so the shape of the display can still be seen
+        draw.polygon().color(rgb(shade, shade, shade)).points(points);
+    }
+}
+
+fn draw_digit_grid(model: &Model, draw: &nannou::app::Draw, rect: Rect) {
+//Shows the prediction for every number in the training data at once, 4 to a row.
+//Wrong segments get a red border, and the number is written in red if any of its segments are wrong.
+    let count = model.data.len();
+    let columns = 4;
+    let rows = (count + columns - 1) / columns;
+    let cell_w = rect.w() / columns as f32;
+    let cell_h = rect.h() / rows as f32;
+    let height = (cell_h * 0.6).min(cell_w * 0.45);
+    let mut wrong_count = 0;
+    for num in 0..count {
+        let x = rect.left() + cell_w * ((num % columns) as f32 + 0.5);
+        let y = rect.top() - cell_h * ((num / columns) as f32 + 0.5);
+        let values = model.network.calculate(&model.data.inputs[num]);
+        let outputs = &values[values.len() - 1];
+        let targets = model.data.outputs[num].clone();
+        let wrong = is_wrong(outputs, &targets);
+        if wrong {
+            wrong_count += 1;
+        }
+        draw_display(draw, x, y + cell_h * 0.08, height, outputs, Some(&targets));
+        let label_color = if wrong {rgb(1.0, 0.3, 0.3)} else {rgb(0.7, 0.7, 0.7)};
+        draw.text(&num.to_string()).x_y(x, y - cell_h * 0.4).w_h(cell_w, 14.0).font_size(11).color(label_color);
+    }
+    let summary = format!("{} of {} numbers shown wrong (G: back to the network)", wrong_count, count);
+    draw.text(&summary).x_y(rect.x(), rect.bottom() - 6.0).w_h(rect.w(), 14.0).font_size(12).color(WHITE);
+}
+
+fn heatmap_rect(window: Rect) -> Rect {
+//A square in the right part of the window, above the plot.
+    let bottom = plot_rect(window).top();
+    let size = (window.w() * 0.4 - 40.0).min(window.top() - bottom - 80.0).max(10.0);
+    Rect::from_x_y_w_h(window.left() + window.w() * 0.8, (window.top() + bottom) / 2.0, size, size)
+}
+
+fn input_ranges(data: &Dataset) -> ((f32, f32), (f32, f32)) {
+//The part of the input plane shown in the heatmap: all the training points, with a bit of space around them.
+    let mut ranges = Vec::new();
+    for input_num in 0..2 {
+        let low = data.inputs.iter().map(|inputs| inputs[input_num]).fold(f32::MAX, f32::min);
+        let high = data.inputs.iter().map(|inputs| inputs[input_num]).fold(f32::MIN, f32::max);
+        let padding = ((high - low) * 0.15).max(0.5);
+        ranges.push((low - padding, high + padding));
+    }
+    (ranges[0], ranges[1])
+}
+
+fn output_color(outputs: &[f32]) -> (f32, f32, f32) {
+//A single output goes from red at 0 to blue at 1, like the weights.
+//With several outputs, each class has its own color, which is brighter the more sure the network is.
+    if outputs.len() == 1 {
+        let value = outputs[0].clamp(0.0, 1.0);
+        return (1.0 + (0.2 - 1.0) * value, 0.25 + (0.5 - 0.25) * value, 0.2 + (1.0 - 0.2) * value)
+    }
+    let colors = [(0.2, 0.5, 1.0), (1.0, 0.25, 0.2), (0.3, 1.0, 0.3), (1.0, 0.6, 0.1), (0.7, 0.3, 1.0), (0.2, 0.9, 0.9), (1.0, 0.9, 0.2), (1.0, 0.4, 0.8)];
+    let class = dataset::decode_one_hot(outputs);
+    let brightness = 0.3 + 0.7 * outputs[class].clamp(0.0, 1.0);
+    let color = colors[class % colors.len()];
+    (color.0 * brightness, color.1 * brightness, color.2 * brightness)
+}
+
+fn draw_heatmap(model: &Model, draw: &nannou::app::Draw, rect: Rect) {
+//Shows the network's outputs for every point of the input plane, with the training points on top in the color of their desired outputs.
+//It is calculated again every frame, so it changes as the network learns. One session is used for all the cells, so nothing is allocated per cell.
+    let resolution = 40;
+    let mut session = InferenceSession::new(&model.network, 2);
+    let (x_range, y_range) = input_ranges(&model.data);
+    let cell_w = rect.w() / resolution as f32;
+    let cell_h = rect.h() / resolution as f32;
+    for row in 0..resolution {
+        for column in 0..resolution {
+            let x = x_range.0 + (x_range.1 - x_range.0) * (column as f32 + 0.5) / resolution as f32;
+            let y = y_range.0 + (y_range.1 - y_range.0) * (row as f32 + 0.5) / resolution as f32;
+            let color = output_color(session.run(&[x, y]));
+            let shade = 0.6; // Darker than the training points, so they stand out
+            draw.rect()
+                .x_y(rect.left() + cell_w * (column as f32 + 0.5), rect.bottom() + cell_h * (row as f32 + 0.5))
+                .w_h(cell_w + 0.5, cell_h + 0.5) // A little overlap, so there are no gaps between the cells
+                .color(rgb(color.0 * shade, color.1 * shade, color.2 * shade));
+        }
+    }
+
+    let position = |inputs: &[f32]| pt2(
+        rect.left() + rect.w() * (inputs[0] - x_range.0) / (x_range.1 - x_range.0),
+        rect.bottom() + rect.h() * (inputs[1] - y_range.0) / (y_range.1 - y_range.0));
+    for num in 0..model.data.len() {
+        let color = output_color(&model.data.outputs[num]);
+        let center = position(&model.data.inputs[num]);
+        draw.ellipse().xy(center).radius(5.0).color(WHITE);
+        draw.ellipse().xy(center).radius(4.0).color(rgb(color.0, color.1, color.2));
+    }
+    let shown = position(&shown_sample(model).0); // The sample shown in the network is circled
+    for num in 0..24 { // A ring of short lines, so the point inside can still be seen
+        let angle = |num: usize| num as f32 * 2.0 * PI / 24.0;
+        let start = shown + pt2(angle(num).cos(), angle(num).sin()) * 9.0;
+        let end = shown + pt2(angle(num + 1).cos(), angle(num + 1).sin()) * 9.0;
+        draw.line().start(start).end(end).thickness(2.0).color(YELLOW);
+    }
+
+    let label = format!("x: {:.1} to {:.1}   y: {:.1} to {:.1}", x_range.0, x_range.1, y_range.0, y_range.1);
+    draw.text(&label).x_y(rect.x(), rect.bottom() - 10.0).w_h(rect.w(), 14.0).font_size(10).color(rgb(0.7, 0.7, 0.7));
 }
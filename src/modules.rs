@@ -0,0 +1,520 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, string::{String, ToString}, vec, vec::Vec};
+
+use crate::float;
+use crate::nodes_layers::{Activation, Module};
+use crate::rng::SimpleRng;
+
+pub fn shape_text(shape: &[usize]) -> String {
+//Writes a shape like [3, 8, 8] as "3,8,8", which is how shapes are written in module descriptions.
+    let parts: Vec<String> = shape.iter().map(|size| size.to_string()).collect();
+    parts.join(",")
+}
+
+#[derive(Clone)]
+pub struct ActivationLayer {
+//Applies an activation function to every value of the previous module. Put it after a Layer with Activation::Identity to split the two steps.
+    pub activation: Activation,
+}
+
+impl ActivationLayer {
+    pub fn new(activation: Activation) -> ActivationLayer {
+        ActivationLayer {
+            activation,
+        }
+    }
+}
+
+impl Module for ActivationLayer {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut values = Vec::new();
+        for num in 0..inputs.len() {
+            values.push(self.activation.apply(inputs[num]));
+        }
+        values
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        for num in 0..inputs.len() {
+            outputs[num] = self.activation.apply(inputs[num]);
+        }
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        input_count
+    }
+
+    fn backward(&mut self, _inputs: &[f32], outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        let mut input_deltas = Vec::new();
+        for num in 0..outputs.len() {
+            input_deltas.push(output_deltas[num] * self.activation.derivative(outputs[num]));
+        }
+        input_deltas
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("activation {}", self.activation.name())
+    }
+
+    fn as_activation_layer(&self) -> Option<&ActivationLayer> {
+        Some(self)
+    }
+}
+
+#[derive(Clone)]
+pub struct Dropout {
+//Sets a random part of the values to 0 while training, so the network can't rely too much on any single node.
+//The remaining values are scaled up, so nothing has to change when the network is used without dropout afterwards.
+    pub rate: f32, //The chance of a value being dropped, in [0; 1[
+    mask: Vec<f32>, //What each value was multiplied by during the last train_forward.
+}
+
+impl Dropout {
+    pub fn new(rate: f32) -> Dropout {
+        if !(0.0..1.0).contains(&rate) {
+            panic!("The dropout rate ({}) must be in [0; 1[", rate);
+        }
+        Dropout {
+            rate,
+            mask: Vec::new(),
+        }
+    }
+}
+
+impl Module for Dropout {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        inputs.to_vec()
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        outputs.copy_from_slice(inputs);
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        input_count
+    }
+
+    fn train_forward(&mut self, inputs: &[f32], rng: &mut SimpleRng) -> Vec<f32> {
+        self.mask = Vec::new();
+        let mut values = Vec::new();
+        for num in 0..inputs.len() {
+            let x = rng.gen_f32();  // Random number in the interval [0; 1[
+            let factor = if x < self.rate {0.0} else {1.0 / (1.0 - self.rate)};
+            self.mask.push(factor);
+            values.push(inputs[num] * factor);
+        }
+        values
+    }
+
+    fn backward(&mut self, inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+    //The mask is used up, so a backward after forward can't pick up the mask of an earlier train_forward.
+        let mask = core::mem::take(&mut self.mask);
+        if mask.len() != inputs.len() {
+            return output_deltas.to_vec() //No mask was made, so forward was used and nothing was dropped.
+        }
+        let mut input_deltas = Vec::new();
+        for num in 0..inputs.len() {
+            input_deltas.push(output_deltas[num] * mask[num]);
+        }
+        input_deltas
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("dropout {}", self.rate)
+    }
+
+    fn passes_values_on(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct Normalization {
+//Layer normalization: shifts and scales the values of the previous module so they have mean 0 and variance 1,
+//then multiplies them by a learned gain and adds a learned shift.
+    pub gains: Vec<f32>,
+    pub shifts: Vec<f32>,
+    pub epsilon: f32, //Keeps the division safe when all values are the same.
+    gain_adjusts: Vec<f32>,
+    shift_adjusts: Vec<f32>,
+}
+
+impl Normalization {
+    pub fn new(size: usize) -> Normalization {
+        Normalization {
+            gains: vec![1.0; size],
+            shifts: vec![0.0; size],
+            epsilon: 1e-5,
+            gain_adjusts: vec![0.0; size],
+            shift_adjusts: vec![0.0; size],
+        }
+    }
+
+    fn statistics(&self, inputs: &[f32]) -> (f32, f32) {
+    //Returns the mean of the inputs and 1 / standard deviation.
+        let size = self.gains.len();
+        if inputs.len() != size {
+            panic!("The normalization has size {}, but received {} values", size, inputs.len());
+        }
+        let mean = inputs.iter().sum::<f32>() / size as f32;
+        let mut variance = 0.0;
+        for num in 0..size {
+            variance += float::square(inputs[num] - mean) / size as f32;
+        }
+        (mean, 1.0 / float::sqrt(variance + self.epsilon))
+    }
+
+    fn normalize(&self, inputs: &[f32]) -> (Vec<f32>, f32) {
+    //Returns the normalized inputs, before gain and shift, along with 1 / standard deviation.
+        let (mean, inverse_deviation) = self.statistics(inputs);
+        let mut normalized = Vec::new();
+        for num in 0..inputs.len() {
+            normalized.push((inputs[num] - mean) * inverse_deviation);
+        }
+        (normalized, inverse_deviation)
+    }
+}
+
+impl Module for Normalization {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let (normalized, _) = self.normalize(inputs);
+        let mut values = Vec::new();
+        for num in 0..normalized.len() {
+            values.push(normalized[num] * self.gains[num] + self.shifts[num]);
+        }
+        values
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        let (mean, inverse_deviation) = self.statistics(inputs);
+        for num in 0..inputs.len() {
+            outputs[num] = (inputs[num] - mean) * inverse_deviation * self.gains[num] + self.shifts[num];
+        }
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        input_count
+    }
+
+    fn backward(&mut self, inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        let (normalized, inverse_deviation) = self.normalize(inputs);
+        let size = normalized.len();
+        let mut normalized_deltas = Vec::new();
+        let mut delta_sum = 0.0;
+        let mut weighted_delta_sum = 0.0;
+        for num in 0..size {
+            self.gain_adjusts[num] += output_deltas[num] * normalized[num];
+            self.shift_adjusts[num] += output_deltas[num];
+            let normalized_delta = output_deltas[num] * self.gains[num];
+            delta_sum += normalized_delta;
+            weighted_delta_sum += normalized_delta * normalized[num];
+            normalized_deltas.push(normalized_delta);
+        }
+        let mut input_deltas = Vec::new();
+        for num in 0..size {
+            input_deltas.push(inverse_deviation / size as f32 * (size as f32 * normalized_deltas[num] - delta_sum - normalized[num] * weighted_delta_sum));
+        }
+        input_deltas
+    }
+
+    fn parameters(&self) -> Vec<f32> {
+    //The gains followed by the shifts.
+        let mut parameters = self.gains.clone();
+        parameters.extend_from_slice(&self.shifts);
+        parameters
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        let size = self.gains.len();
+        if parameters.len() != 2 * size {
+            panic!("The number of parameters ({}) doesn't match the normalization ({})", parameters.len(), 2 * size);
+        }
+        self.gains = parameters[..size].to_vec();
+        self.shifts = parameters[size..].to_vec();
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+        let mut gradients = self.gain_adjusts.clone();
+        gradients.extend_from_slice(&self.shift_adjusts);
+        gradients
+    }
+
+    fn adjust(&mut self, learning_rate: f32) {
+        for num in 0..self.gains.len() {
+            self.gains[num] -= self.gain_adjusts[num] * learning_rate;
+            self.shifts[num] -= self.shift_adjusts[num] * learning_rate;
+            self.gain_adjusts[num] = 0.0;
+            self.shift_adjusts[num] = 0.0;
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("normalization {} {}", self.gains.len(), self.epsilon)
+    }
+}
+
+#[derive(Clone)]
+pub struct Reshape {
+//Changes how the values are meant to be read, e.g. from 3 channels of 8x8 pixels to 192 values in a row.
+//The values are always stored in one list, so only the sizes are checked and the values are passed on unchanged.
+    pub input_shape: Vec<usize>,
+    pub output_shape: Vec<usize>,
+}
+
+impl Reshape {
+    pub fn new(input_shape: Vec<usize>, output_shape: Vec<usize>) -> Reshape {
+        let input_size: usize = input_shape.iter().product();
+        let output_size: usize = output_shape.iter().product();
+        if input_size != output_size {
+            panic!("Can't reshape {:?} ({} values) into {:?} ({} values)", input_shape, input_size, output_shape, output_size);
+        }
+        Reshape {
+            input_shape,
+            output_shape,
+        }
+    }
+}
+
+impl Module for Reshape {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let input_size: usize = self.input_shape.iter().product();
+        if inputs.len() != input_size {
+            panic!("Expected {} values with the shape {:?}, but received {}", input_size, self.input_shape, inputs.len());
+        }
+        inputs.to_vec()
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        let input_size: usize = self.input_shape.iter().product();
+        if inputs.len() != input_size {
+            panic!("Expected {} values with the shape {:?}, but received {}", input_size, self.input_shape, inputs.len());
+        }
+        outputs.copy_from_slice(inputs);
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        input_count
+    }
+
+    fn backward(&mut self, _inputs: &[f32], _outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+        output_deltas.to_vec()
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        format!("reshape {} {}", shape_text(&self.input_shape), shape_text(&self.output_shape))
+    }
+
+    fn passes_values_on(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone)]
+pub struct Softmax {
+//Turns the values of the previous module into probabilities that are all positive and add up to 1.
+//Put it at the end of a Network with Cost::CrossEntropy for classification.
+}
+
+impl Softmax {
+    pub fn new() -> Softmax {
+        Softmax {}
+    }
+}
+
+impl Default for Softmax {
+    fn default() -> Softmax {
+        Softmax::new()
+    }
+}
+
+impl Module for Softmax {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let max = inputs.iter().cloned().fold(f32::NEG_INFINITY, f32::max); //Subtracting the largest value keeps exp from overflowing.
+        let mut values = Vec::new();
+        let mut sum = 0.0;
+        for num in 0..inputs.len() {
+            let value = float::exp(inputs[num] - max);
+            sum += value;
+            values.push(value);
+        }
+        for num in 0..values.len() {
+            values[num] /= sum;
+        }
+        values
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        let max = inputs.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let mut sum = 0.0;
+        for num in 0..inputs.len() {
+            let value = float::exp(inputs[num] - max);
+            sum += value;
+            outputs[num] = value;
+        }
+        for num in 0..outputs.len() {
+            outputs[num] /= sum;
+        }
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+        input_count
+    }
+
+    fn backward(&mut self, _inputs: &[f32], outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+    //Every input changes every output, so each input delta depends on all the output deltas.
+    //Together with Cost::CrossEntropy the input deltas become outputs - desired_outputs.
+        let mut weighted_sum = 0.0;
+        for num in 0..outputs.len() {
+            weighted_sum += output_deltas[num] * outputs[num];
+        }
+        let mut input_deltas = Vec::new();
+        for num in 0..outputs.len() {
+            input_deltas.push(outputs[num] * (output_deltas[num] - weighted_sum));
+        }
+        input_deltas
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        "softmax".to_string()
+    }
+
+    fn as_softmax(&self) -> Option<&Softmax> {
+        Some(self)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::nodes_layers::tests::{check_gradients, test_inputs};
+    use crate::nodes_layers::{Cost, Layer, Network};
+
+    #[test]
+    fn normalization_gradients_match_finite_differences() {
+        let mut normalization = Normalization::new(6);
+        normalization.set_parameters(&[1.2, 0.7, -0.4, 1.0, 0.3, -1.1, 0.2, -0.5, 0.0, 0.9, -0.3, 0.4]);
+        check_gradients(&mut normalization, &test_inputs(6), 1e-2);
+    }
+
+    #[test]
+    fn dropout_only_drops_while_training() {
+        let mut dropout = Dropout::new(0.5);
+        let inputs = test_inputs(20);
+        assert_eq!(dropout.forward(&inputs), inputs);
+
+        let mut rng = SimpleRng::new(2);
+        let outputs = dropout.train_forward(&inputs, &mut rng);
+        let ones = vec![1.0; inputs.len()];
+        let deltas = dropout.backward(&inputs, &outputs, &ones);
+        assert!(deltas.contains(&0.0) && deltas.contains(&2.0));
+        for num in 0..inputs.len() {
+            //The deltas are the mask, which the outputs were multiplied by.
+            assert_eq!(outputs[num], inputs[num] * deltas[num]);
+        }
+
+        let outputs = dropout.forward(&inputs);
+        assert_eq!(dropout.backward(&inputs, &outputs, &ones), ones);
+    }
+
+    #[test]
+    fn reshape_passes_values_and_deltas_on() {
+        let mut reshape = Reshape::new(vec![2, 3], vec![6]);
+        let inputs = test_inputs(6);
+        assert_eq!(reshape.forward(&inputs), inputs);
+        assert_eq!(reshape.backward(&inputs, &inputs, &test_inputs(6)), test_inputs(6));
+    }
+
+    #[test]
+    #[should_panic]
+    fn reshape_to_another_size_panics() {
+        Reshape::new(vec![2, 3], vec![5]);
+    }
+
+    fn classifier() -> Network {
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer::from_rng(4, 3, Activation::Identity, &mut SimpleRng::new(6))));
+        network.push(Box::new(Softmax::new()));
+        network.cost = Cost::CrossEntropy;
+        network
+    }
+
+    #[test]
+    fn softmax_outputs_are_probabilities() {
+        let outputs = Softmax::new().forward(&[1.0, -2.0, 0.5, 100.0]);
+        assert!((outputs.iter().sum::<f32>() - 1.0).abs() < 1e-6);
+        assert!(outputs.iter().all(|value| *value >= 0.0));
+        assert_eq!(crate::dataset::decode_one_hot(&outputs), 3);
+        check_gradients(&mut Softmax::new(), &test_inputs(5), 1e-2);
+    }
+
+    #[test]
+    fn softmax_with_cross_entropy_gradients_match_finite_differences() {
+        let step = 0.01;
+        let inputs = [0.3, -0.8, 0.5, 1.0];
+        let desired_outputs = [0.0, 1.0, 0.0];
+        let mut network = classifier();
+        let values = network.calculate(&inputs);
+        let input_deltas = network.find_adjusts(&inputs, &desired_outputs);
+        let gradients = network.layers[0].gradients();
+
+        //Together the two give the deltas outputs - desired_outputs for the weighted sums of the layer.
+        let layer = network.layers[0].as_layer().unwrap();
+        for node_num in 0..3 {
+            let expected = values[1][node_num] - desired_outputs[node_num];
+            assert!((layer.nodes[node_num].bias_adjust.unwrap() - expected).abs() < 1e-6);
+        }
+
+        let parameters = network.layers[0].parameters();
+        for num in 0..parameters.len() {
+            let mut moved = network.clone();
+            let mut moved_parameters = parameters.clone();
+            moved_parameters[num] = parameters[num] + step;
+            moved.layers[0].set_parameters(&moved_parameters);
+            let higher = moved.find_cost(&inputs, &desired_outputs);
+            moved_parameters[num] = parameters[num] - step;
+            moved.layers[0].set_parameters(&moved_parameters);
+            let lower = moved.find_cost(&inputs, &desired_outputs);
+            let expected = (higher - lower) / (2.0 * step);
+            assert!((gradients[num] - expected).abs() < 1e-2, "parameter {}: {} vs {}", num, gradients[num], expected);
+        }
+        for num in 0..inputs.len() {
+            let mut moved = inputs;
+            moved[num] = inputs[num] + step;
+            let higher = network.find_cost(&moved, &desired_outputs);
+            moved[num] = inputs[num] - step;
+            let lower = network.find_cost(&moved, &desired_outputs);
+            let expected = (higher - lower) / (2.0 * step);
+            assert!((input_deltas[num] - expected).abs() < 1e-2, "input {}: {} vs {}", num, input_deltas[num], expected);
+        }
+    }
+
+    #[test]
+    fn predict_class_is_the_most_likely_class() {
+        let network = classifier();
+        let inputs = [0.3, -0.8, 0.5, 1.0];
+        let (class, probabilities) = network.predict_class(&inputs);
+        let values = network.calculate(&inputs);
+        assert_eq!(probabilities, values[1]);
+        assert!(probabilities.iter().all(|probability| *probability <= probabilities[class]));
+    }
+}
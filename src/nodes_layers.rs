@@ -1,17 +1,164 @@
-extern crate rand;
-
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+#[cfg(feature = "std")]
 use rand::Rng;
 
+#[cfg(feature = "std")]
+use crate::dataset;
+use crate::float;
+use crate::modules::{ActivationLayer, Softmax};
+use crate::rng::SimpleRng;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Activation {
+//The function a node uses to turn its weighted sum into its value.
+    Sigmoid,
+    Tanh,
+    Relu,
+    Identity,
+}
+
+impl Activation {
+    pub fn apply(&self, value: f32) -> f32 {
+    //Applies the activation function to a weighted sum.
+        match self {
+            Activation::Sigmoid => {
+                let norm_value = 1.0 / (1.0 + float::exp(-value));
+                if !(0.0..=1.0).contains(&norm_value) {
+                    panic!{"Math is broken, the sigmoid functions returns value outside [0; 1]"}
+                }
+                norm_value
+            },
+            Activation::Tanh => float::tanh(value),
+            Activation::Relu => if value > 0.0 {value} else {0.0},
+            Activation::Identity => value,
+        }
+    }
+
+    pub fn derivative(&self, personal_value: f32) -> f32 {
+    //The derivative of the activation function, found from the value the function returned.
+    //For the sigmoid this is the personal_value * (1.0 - personal_value) from the back propagation note.
+        match self {
+            Activation::Sigmoid => personal_value * (1.0 - personal_value),
+            Activation::Tanh => 1.0 - personal_value * personal_value,
+            Activation::Relu => if personal_value > 0.0 {1.0} else {0.0},
+            Activation::Identity => 1.0,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Activation::Sigmoid => "sigmoid",
+            Activation::Tanh => "tanh",
+            Activation::Relu => "relu",
+            Activation::Identity => "identity",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Activation> {
+        match name {
+            "sigmoid" => Some(Activation::Sigmoid),
+            "tanh" => Some(Activation::Tanh),
+            "relu" => Some(Activation::Relu),
+            "identity" => Some(Activation::Identity),
+            _ => None,
+        }
+    }
+}
+
+pub trait Module {
+//A building block of a Network, like a dense layer, an activation function or a dropout.
+//Every module turns the values of the previous module into its own values, and can find out how its parameters
+//should be adjusted from how the cost changes with its own values.
+    fn forward(&self, inputs: &[f32]) -> Vec<f32>;
+    //Calculates the values of the module based on the values of the previous module.
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+    //Like forward, but writes the values into outputs, which must have room for exactly as many as forward gives.
+    //Used by InferenceSession to calculate without allocating. This default calls forward, so modules override it to avoid the allocations.
+        outputs.copy_from_slice(&self.forward(inputs));
+    }
+
+    fn output_count(&self, input_count: usize) -> usize {
+    //The number of values forward gives for input_count inputs.
+        self.forward(&vec![0.0; input_count]).len()
+    }
+
+    fn train_forward(&mut self, inputs: &[f32], _rng: &mut SimpleRng) -> Vec<f32> {
+    //Like forward, but used during training. Modules that behave differently while training, like Dropout, override it.
+    //The random number generator belongs to the Network, so a whole training run can be repeated from a seed.
+        self.forward(inputs)
+    }
+
+    fn backward(&mut self, inputs: &[f32], outputs: &[f32], output_deltas: &[f32]) -> Vec<f32>;
+    //Takes how the cost changes with each of the module's values (output_deltas) and adds to the adjusts of the module's parameters.
+    //Returns how the cost changes with each of the values of the previous module, so the previous module can do the same.
+
+    fn parameters(&self) -> Vec<f32> {
+    //All of the module's biases and weights in one list. Modules without parameters return an empty list.
+        Vec::new()
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+    //Overwrites the module's parameters with a list in the same order as the one returned by parameters.
+        if !parameters.is_empty() {
+            panic!("The module has no parameters, but received {}", parameters.len());
+        }
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+    //The adjusts found by backward, in the same order as parameters.
+        Vec::new()
+    }
+
+    fn adjust(&mut self, _learning_rate: f32) {
+    //Changes the parameters after backward has found how they're supposed to be adjusted.
+    }
+
+    fn box_clone(&self) -> Box<dyn Module>;
+    //Makes it possible to clone a Network, which only knows its modules as Box<dyn Module>.
+
+    fn describe(&self) -> String;
+    //One line with the kind of module and its sizes, e.g. "layer 4 8 sigmoid". Used by checkpoint to save and rebuild the module.
+
+    fn as_layer(&self) -> Option<&Layer> {
+    //Gives access to the nodes of dense layers, e.g. so their weights can be drawn. Other modules return None.
+        None
+    }
+
+    fn as_activation_layer(&self) -> Option<&ActivationLayer> {
+    //Like as_layer, for activation layers. Used by the exports, which need to know which function to write.
+        None
+    }
+
+    fn as_softmax(&self) -> Option<&Softmax> {
+        None
+    }
+
+    fn passes_values_on(&self) -> bool {
+    //True for modules whose forward gives back its inputs unchanged, like Dropout outside training, Flatten and Reshape,
+    //so the exports can leave them out.
+        false
+    }
+}
+
+impl Clone for Box<dyn Module> {
+    fn clone(&self) -> Box<dyn Module> {
+        self.box_clone()
+    }
+}
+
 #[derive(Clone)]
-struct Node {
+pub struct Node {
 // A node/neuron's bias and the weights of its connections to the previous layer.
-    bias: f32,
-    weights: Vec<f32>,
-    bias_adjust: Option<f32>,
-    weight_adjusts: Vec<f32>,
+    pub bias: f32,
+    pub weights: Vec<f32>,
+    pub bias_adjust: Option<f32>,
+    pub weight_adjusts: Vec<f32>,
 }
 
 impl Node {
+    #[cfg(feature = "std")]
     pub fn new(number_of_weights: usize) -> Node {
     //Generates a new node with a random bias and random weights.
         let mut rng = rand::thread_rng();
@@ -26,15 +173,31 @@ impl Node {
         let bias = 2.0 * x  - 1.0;  // The initial weights will be in [-1; 1[
 
         Node {
-            bias: bias,
+            bias,
             weights: init_weights, // vec![0.0; number_of_weights],
             bias_adjust: None,
             weight_adjusts: Vec::new()
         }
     }
 
-    pub fn calculate(&self, previous_layer_values:&Vec<f32>) -> f32 {
-    //Calculates the value of the node based on the values of the previous layer and the node's bias and weights.
+    pub fn from_rng(number_of_weights: usize, rng: &mut SimpleRng) -> Node {
+    //Like new, but the random bias and weights come from rng, so the same seed always gives the same node.
+        let mut init_weights = vec![0.0; number_of_weights];
+        for n in 0..number_of_weights {
+            init_weights[n] = rng.gen_range(-1.0, 1.0);
+        }
+        let bias = rng.gen_range(-1.0, 1.0);
+
+        Node {
+            bias,
+            weights: init_weights,
+            bias_adjust: None,
+            weight_adjusts: Vec::new()
+        }
+    }
+
+    pub fn weighted_sum(&self, previous_layer_values:&[f32]) -> f32 {
+    //Calculates the bias plus the values of the previous layer multiplied by the weights, before any activation function is used.
         let mut value = self.bias;
         let previous_layer_len = previous_layer_values.len();
         if self.weights.len() != previous_layer_len {
@@ -43,224 +206,444 @@ impl Node {
         for pos_num in 0..previous_layer_len {
             value += previous_layer_values[pos_num] * self.weights[pos_num];
         }
-        let norm_value = 1.0 / (1.0 + (-value).exp());
-        if norm_value < 0.0 || norm_value > 1.0 {
-            panic!{"Math is broken, the sigmoid functions returns value outside [0; 1]"}
-        }
-        norm_value
-    }
-
-    // pub fn find_adjusts(&mut self, previous_layer_values:&Vec<f32>, personal_value:f32, desired_value:f32, next_layer: &Layer) {
-    // //Finds out how the node's bias and weights should be adjusted based on either a known desired value or how the next layer is set to be adjusted.
-    // //If it receives a next_layer with any nodes in it, it uses the bias_adjusts of the nodes in that layer that connect with it along with its personal_value to find its own bias_adjust.
-    // //Otherwise, it finds its bias_adjust from its personal_value and a specified desired_value.
-    // //It then finds its weight_adjusts from its bias_adjust and previous_layer_values.
-    //     if self.bias_adjust.is_some() {
-    //         panic!("The bias adjust contained a value before adjusts were found");
-    //     }
-    //     if self.weight_adjusts.len() != 0 {
-    //         panic!("The weight adjusts contained {} value(s) before adjusts were found", self.weight_adjusts.len());
-    //     }
-    //     let previous_layer_len = previous_layer_values.len();
-    //     if self.weights.len() != previous_layer_len {
-    //         panic!("The number of weights ({}) doesn't match the number of values ({})", self.weights.len(), previous_layer_len);
-    //     }
-    //     let mut delta = 0.0;
-    //     if next_layer.node_count == 0 {
-    //         delta = (personal_value - desired_value) * personal_value * (1.0 - personal_value)
-    //     } else {
-    //         for num in 0..next_layer.node_count {
-    //             delta += next_layer.nodes[num].bias_adjust.unwrap() * next_layer.nodes[num].weights[desired_value as usize]; //If there is a next_layer, desired_value tracks which connections in it lead to the node.
-    //         }
-    //         delta *= personal_value * (1.0 - personal_value);
-    //     }
-    //     self.bias_adjust = Some(delta);
-    //     for pos_num in 0..previous_layer_len {
-    //         self.weight_adjusts.push(delta * previous_layer_values[pos_num]);
-    //     }
-    // }
-    //
-    // pub fn adjust(&mut self, learning_rate: f32) {
-    // //Changes the weights and biases after all nodes have found how they're supposed to be adjusted.
-    //     if self.bias_adjust.is_none() {
-    //         panic!{"Bias adjust is missing"}
-    //     }
-    //     let weight_count = self.weights.len();
-    //     if self.weight_adjusts.len() != weight_count {
-    //         panic!("The number of weight adjusts ({}) doesn't match the number of weights ({})", self.weight_adjusts.len(), weight_count);
-    //     }
-    //     self.bias -= self.bias_adjust.unwrap() * learning_rate;
-    //     self.bias_adjust = None;
-    //     for num in 0..weight_count {
-    //         self.weights[num] -= self.weight_adjusts[num] * learning_rate;
-    //     }
-    //     self.weight_adjusts = Vec::new();
-    // }
-
-    pub fn find_delta(&self, personal_value:f32, desired_value:f32, next_layer: &Layer, next_layer_deltas: &Vec<f32>) -> f32 {
-        //Finds delta and returns it.
-        let mut delta = 0.0;
-        if next_layer.node_count == 0 {
-            delta = (personal_value - desired_value) * personal_value * (1.0 - personal_value)
-        } else {
-            for num in 0..next_layer.node_count {
-                delta += next_layer_deltas[num] * next_layer.nodes[num].weights[desired_value as usize]; //If there is a next_layer, desired_value tracks which connections in it lead to the node.
-            }
-            delta *= personal_value * (1.0 - personal_value);
+        value
+    }
+
+    pub fn calculate(&self, previous_layer_values:&[f32]) -> f32 {
+    //Calculates the value of the node based on the values of the previous layer and the node's bias and weights.
+        Activation::Sigmoid.apply(self.weighted_sum(previous_layer_values))
+    }
+
+    pub fn add_adjusts(&mut self, delta: f32, previous_layer_values:&[f32]) {
+    //Adds to the node's bias_adjust and weight_adjusts based on its delta, which is how the cost changes with the node's weighted sum.
+    //The adjusts are added rather than overwritten, so several samples can be found before adjust is called.
+        let previous_layer_len = previous_layer_values.len();
+        if self.weights.len() != previous_layer_len {
+            panic!("The number of weights ({}) doesn't match the number of values ({})", self.weights.len(), previous_layer_len);
+        }
+        self.bias_adjust = Some(self.bias_adjust.unwrap_or(0.0) + delta);
+        if self.weight_adjusts.is_empty() {
+            self.weight_adjusts = vec![0.0; previous_layer_len];
+        }
+        for pos_num in 0..previous_layer_len {
+            self.weight_adjusts[pos_num] += delta * previous_layer_values[pos_num];
         }
-        delta
     }
 
-    // pub fn single_adjust(&mut self, delta: f32, learning_rate: f32, previous_layer_value: f32, relevant_weight: usize) {
-    //     if relevant_weight == self.weights.len() {
-    //         self.bias -= delta * learning_rate;
-    //     } else {
-    //         self.weights[relevant_weight] -= delta * previous_layer_value * learning_rate;
-    //     }
-    // }
+    pub fn adjust(&mut self, learning_rate: f32) {
+    //Changes the weights and biases after all nodes have found how they're supposed to be adjusted.
+        if self.bias_adjust.is_none() {
+            panic!{"Bias adjust is missing"}
+        }
+        let weight_count = self.weights.len();
+        if self.weight_adjusts.len() != weight_count {
+            panic!("The number of weight adjusts ({}) doesn't match the number of weights ({})", self.weight_adjusts.len(), weight_count);
+        }
+        self.bias -= self.bias_adjust.unwrap() * learning_rate;
+        self.bias_adjust = None;
+        for num in 0..weight_count {
+            self.weights[num] -= self.weight_adjusts[num] * learning_rate;
+        }
+        self.weight_adjusts = Vec::new();
+    }
 }
 
 #[derive(Clone)]
-struct Layer {
-//A layer of nodes, their biases, and the weights of their connections to the previous layer.
-    nodes: Vec<Node>,
-    node_count: usize, //Should be equal to nodes.len() and shouldn't change.
+pub struct Layer {
+//A dense layer of nodes, their biases, and the weights of their connections to the previous layer.
+    pub nodes: Vec<Node>,
+    pub node_count: usize, //Should be equal to nodes.len() and shouldn't change.
+    pub activation: Activation,
 }
 
 impl Layer {
+    #[cfg(feature = "std")]
     pub fn new(previous_layer_nodes: usize, number_of_nodes: usize) -> Layer {
-    //Generates a layer of nodes, each with a random bias and a number of random weights equal to the number of nodes in the previous layer.
+    //Generates a layer of sigmoid nodes, each with a random bias and a number of random weights equal to the number of nodes in the previous layer.
+        Layer::with_activation(previous_layer_nodes, number_of_nodes, Activation::Sigmoid)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_activation(previous_layer_nodes: usize, number_of_nodes: usize, activation: Activation) -> Layer {
+    //Like new, but with a different activation function. Activation::Identity gives a plain linear layer.
         let mut nodes = Vec::new();
         for _ in 0..number_of_nodes {
             nodes.push(Node::new(previous_layer_nodes));
         }
         let node_count = nodes.len();
         Layer {
-            nodes: nodes,
-            node_count: node_count,
+            nodes,
+            node_count,
+            activation,
+        }
+    }
+
+    pub fn from_rng(previous_layer_nodes: usize, number_of_nodes: usize, activation: Activation, rng: &mut SimpleRng) -> Layer {
+    //Like with_activation, but the random biases and weights come from rng.
+        let mut nodes = Vec::new();
+        for _ in 0..number_of_nodes {
+            nodes.push(Node::from_rng(previous_layer_nodes, rng));
+        }
+        let node_count = nodes.len();
+        Layer {
+            nodes,
+            node_count,
+            activation,
         }
     }
 
-    pub fn calculate(&self, previous_layer_values:&Vec<f32>) -> Vec<f32> {
+    pub fn calculate(&self, previous_layer_values:&[f32]) -> Vec<f32> {
     //Calculates the values of the nodes based on the values of the previous layer and the nodes' weights and biases.
         let mut values = Vec::new();
         for node_num in 0..self.node_count {
-            values.push(self.nodes[node_num].calculate(previous_layer_values));
+            values.push(self.activation.apply(self.nodes[node_num].weighted_sum(previous_layer_values)));
         }
         values
     }
 
-    // pub fn find_adjusts(&mut self, previous_layer_values:&Vec<f32>, values:&Vec<f32>, desired_values:&Vec<f32>, next_layer: &Layer) {
-    // //Finds out how the nodes' weights and biases should be adjusted, based either on a list of desired values, or how the next layer is set to be adjusted.
-    // //The function does this by calling find_adjusts for each node.
-    //     for node_num in 0..self.node_count {
-    //         self.nodes[node_num].find_adjusts(
-    //                                           previous_layer_values,
-    //                                           values[node_num], //personal_value, is used to track the calculated value of the relevant node.
-    //                                           if next_layer.node_count > 0 {node_num as f32} //If the next layer contains anything, desired_value is used to track the position of the relevant node.
-    //                                             else {desired_values[node_num]}, //Otherwise, desired_value is used to track the desired value.
-    //                                           next_layer
-    //                                           );
-    //     }
-    // }
-    //
-    // pub fn adjust(&mut self, learning_rate:f32) {
-    // //Calls the adjust function of every node in the layer, which does the following for the node:
-    // //Changes the weights and biases after all nodes have found how they're supposed to be adjusted.
-    //     for node_num in 0..self.node_count {
-    //         self.nodes[node_num].adjust(learning_rate)
-    //     }
-    // }
-
-    pub fn find_deltas(&self, values:&Vec<f32>, desired_values:&Vec<f32>, next_layer:&Layer, next_layer_deltas:&Vec<f32>) -> Vec<f32> {
-    //Finds out how the nodes' weights and biases should be adjusted, based either on a list of desired values, or how the next layer is set to be adjusted.
-    //The function does this by calling find_adjusts for each node.
-        let deltas = Vec::new();
+    #[cfg(feature = "std")]
+    pub(crate) fn check_shape(&self, input_count: usize) -> Result<(), String> {
+    //Makes sure there are node_count nodes, at least one, and that every node has a weight for each of the input_count values before it.
+    //The exports index the nodes and weights directly, so they check this first to give an error instead of a panic.
+    //The error finishes a sentence about the layer, e.g. "Layer 2 " followed by it.
+        if self.node_count == 0 || self.nodes.len() != self.node_count {
+            return Err(format!("has {} nodes instead of {}", self.nodes.len(), self.node_count))
+        }
+        if self.nodes.iter().any(|node| node.weights.len() != input_count) {
+            return Err(format!("needs {} weights in every node, one for each value of the layer before", input_count))
+        }
+        Ok(())
+    }
+}
+
+impl Module for Layer {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        self.calculate(inputs)
+    }
+
+    fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+    //The same as calculate, with the sums added in the same order.
         for node_num in 0..self.node_count {
-            deltas.push(self.nodes[node_num].find_delta(
-                                                        values[node_num],
-                                                        if next_layer.node_count > 0 {node_num as f32} //If the next layer contains anything, desired_value is used to track the position of the relevant node.
-                                                            else {desired_values[node_num]}, //Otherwise, desired_value is used to track the desired value.
-                                                        next_layer,
-                                                        next_layer_deltas
-                                                        )
-                        );
+            let node = &self.nodes[node_num];
+            if node.weights.len() != inputs.len() {
+                panic!("The number of weights ({}) doesn't match the number of values ({})", node.weights.len(), inputs.len());
+            }
+            let mut value = node.bias;
+            for pos_num in 0..inputs.len() {
+                value += inputs[pos_num] * node.weights[pos_num];
+            }
+            outputs[node_num] = self.activation.apply(value);
         }
-        deltas
     }
 
-    pub fn alt_adjust(&mut self, deltas: Vec<f32>, previous_layer_values: Vec<f32>, learning_rate: f32) {
-        let previous_layer_len = previous_layer_values.len();
-        for prev_num in 0..previous_layer_len {
-            for num in 0..self.node_count {
-                self.nodes[num].weights[prev_num] -= deltas[num] * previous_layer_values[prev_num] * learning_rate;
+    fn output_count(&self, _input_count: usize) -> usize {
+        self.node_count
+    }
+
+    fn backward(&mut self, inputs: &[f32], outputs: &[f32], output_deltas: &[f32]) -> Vec<f32> {
+    //Finds each node's delta from how the cost changes with its value, and uses it to find the node's adjusts.
+    //The deltas of the previous layer are the sums of these deltas multiplied by the weights leading to each previous node.
+        if output_deltas.len() != self.node_count {
+            panic!("The number of deltas ({}) doesn't match the number of nodes ({})", output_deltas.len(), self.node_count);
+        }
+        let mut input_deltas = vec![0.0; inputs.len()];
+        for node_num in 0..self.node_count {
+            let delta = output_deltas[node_num] * self.activation.derivative(outputs[node_num]);
+            for prev_num in 0..inputs.len() {
+                input_deltas[prev_num] += delta * self.nodes[node_num].weights[prev_num];
             }
+            self.nodes[node_num].add_adjusts(delta, inputs);
         }
-        for num in 0..self.node_count {
-            self.nodes[num].bias -= deltas[num] * learning_rate;
+        input_deltas
+    }
+
+    fn parameters(&self) -> Vec<f32> {
+    //The bias of each node followed by its weights.
+        let mut parameters = Vec::new();
+        for node in self.nodes.iter() {
+            parameters.push(node.bias);
+            parameters.extend_from_slice(&node.weights);
         }
+        parameters
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        if parameters.len() != self.parameters().len() {
+            panic!("The number of parameters ({}) doesn't match the layer ({})", parameters.len(), self.parameters().len());
+        }
+        let mut pos = 0;
+        for node in self.nodes.iter_mut() {
+            node.bias = parameters[pos];
+            pos += 1;
+            for weight_num in 0..node.weights.len() {
+                node.weights[weight_num] = parameters[pos];
+                pos += 1;
+            }
+        }
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+        let mut gradients = Vec::new();
+        for node in self.nodes.iter() {
+            gradients.push(node.bias_adjust.unwrap_or(0.0));
+            if node.weight_adjusts.is_empty() {
+                gradients.extend_from_slice(&vec![0.0; node.weights.len()]);
+            } else {
+                gradients.extend_from_slice(&node.weight_adjusts);
+            }
+        }
+        gradients
+    }
+
+    fn adjust(&mut self, learning_rate: f32) {
+    //Calls the adjust function of every node in the layer.
+        for node_num in 0..self.node_count {
+            self.nodes[node_num].adjust(learning_rate)
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn Module> {
+        Box::new(self.clone())
+    }
+
+    fn describe(&self) -> String {
+        let previous_layer_nodes = if self.node_count > 0 {self.nodes[0].weights.len()} else {0};
+        format!("layer {} {} {}", previous_layer_nodes, self.node_count, self.activation.name())
+    }
+
+    fn as_layer(&self) -> Option<&Layer> {
+        Some(self)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Cost {
+//How the difference between the outputs and the desired outputs is measured.
+    SquaredError, //Half the sum of the squared differences. Used for independent outputs, like the segments of the 7-segment display.
+    CrossEntropy, //Minus the sum of desired_value * ln(value). Used with a Softmax module at the end, when exactly one output should be 1.
+}
+
+impl Cost {
+    pub fn find(&self, outputs: &[f32], desired_outputs: &[f32]) -> f32 {
+    //Finds the cost of one sample.
+        let mut cost = 0.0;
+        for num in 0..outputs.len() {
+            cost += match self {
+                Cost::SquaredError => float::square(outputs[num] - desired_outputs[num])/2.0,
+                Cost::CrossEntropy => -desired_outputs[num] * float::ln(outputs[num].max(1e-30)),
+            };
+        }
+        cost
+    }
+
+    pub fn deltas(&self, outputs: &[f32], desired_outputs: &[f32]) -> Vec<f32> {
+    //How the cost changes with each output.
+        let mut deltas = Vec::new();
+        for num in 0..outputs.len() {
+            deltas.push(match self {
+                Cost::SquaredError => outputs[num] - desired_outputs[num],
+                Cost::CrossEntropy => -desired_outputs[num] / outputs[num].max(1e-30),
+            });
+        }
+        deltas
     }
 }
 
-struct Network {
-    layers: Vec<Layer>,
-    layer_count: usize,
-    learning_rate: f32,
+#[derive(Clone)]
+pub struct Network {
+//A sequence of modules, where the values of each module are used as the inputs of the next.
+    pub layers: Vec<Box<dyn Module>>,
+    pub layer_count: usize, //Should be equal to layers.len().
+    pub learning_rate: f32,
+    pub cost: Cost,
+    pub rng: SimpleRng, //Used by modules like Dropout while training.
 }
 
 impl Network {
+    #[cfg(feature = "std")]
     pub fn new(node_nums:Vec<usize>, learning_rate: f32) -> Network {
-        let mut layers = Vec::new();
+    //Generates a network of dense sigmoid layers. node_nums[0] is the number of inputs, the rest are the number of nodes in each layer.
+        let mut network = Network::empty(learning_rate);
         for layer_num in 1..node_nums.len() {
-            layers.push(Layer::new(layer_num-1, layer_num));
+            network.push(Box::new(Layer::new(node_nums[layer_num-1], node_nums[layer_num])));
         }
-        let layer_count = layers.len();
+        network
+    }
+
+    pub fn with_seed(node_nums:Vec<usize>, learning_rate: f32, seed: u64) -> Network {
+    //Like new, but the weights, the biases and the network's rng all come from the seed, so a training run can be repeated exactly.
+        let mut network = Network::empty(learning_rate);
+        let mut rng = SimpleRng::new(seed);
+        for layer_num in 1..node_nums.len() {
+            network.push(Box::new(Layer::from_rng(node_nums[layer_num-1], node_nums[layer_num], Activation::Sigmoid, &mut rng)));
+        }
+        network.rng = rng;
+        network
+    }
+
+    pub fn empty(learning_rate: f32) -> Network {
+    //Generates a network without any modules. Use push to add them.
         Network {
-            layers: layers,
-            layer_count: layer_count,
-            learning_rate: learning_rate,
+            layers: Vec::new(),
+            layer_count: 0,
+            learning_rate,
+            cost: Cost::SquaredError,
+            rng: default_rng(),
         }
     }
 
-    pub fn calculate(&self, inputs: &Vec<f32>) -> Vec<Vec<f32>> {
-    //Calculates the values of all nodes based on the active training data and the weights and biases.
+    pub fn push(&mut self, layer: Box<dyn Module>) {
+    //Adds a module to the end of the network.
+        self.layers.push(layer);
+        self.layer_count = self.layers.len();
+    }
+
+    pub fn calculate(&self, inputs: &[f32]) -> Vec<Vec<f32>> {
+    //Calculates the values of all nodes based on the inputs and the weights and biases.
     //The outer vector of the output is the layer, the inner vector is the position in the layer. To get the output layer from values, say values[values.len() - 1]
-        let mut values = vec![self.layers[0].calculate(inputs)];
+        let mut values = vec![self.layers[0].forward(inputs)];
         for num in 1..self.layer_count {
-            values.push(self.layers[num].calculate(&values[num-1]));
+            let next_values = self.layers[num].forward(&values[num-1]);
+            values.push(next_values);
         }
         values
     }
 
-    // pub fn find_make_adjust(&mut self, inputs: &Vec<f32>, desired_outputs:&Vec<f32>) {
-    // //Finds out how the weights and biases should be adjusted, based on the difference between the results of the calculate function and the desired outputs.
-    // //It does it in reverse order, because that's how you have to do it.
-    // //Then it implements all of the changes after they have all been calculated. That doesn't have to be done in reverse order, so it isn't.
-    //     let values = self.calculate(inputs);
-    //     self.layers[self.layer_count-1].find_adjusts(&values[self.layer_count-2], &values[self.layer_count-1], desired_outputs, &Layer::new(0, 0));
-    //     for num in (1..self.layer_count-1).rev() {
-    //         let next_layer = self.layers[num+1].clone();
-    //         self.layers[num].find_adjusts(&values[num-1], &values[num], &Vec::new(), &next_layer);
-    //     }
-    //     let next_layer = self.layers[1].clone();
-    //     self.layers[0].find_adjusts(inputs, &values[0], &Vec::new(), &next_layer);
-    //
-    //     for num in 0..self.layer_count {
-    //         self.layers[num].adjust(self.learning_rate);
-    //     }
-    // }
-
-    pub fn alt_find_make_adjust(&mut self, inputs: &Vec<f32>, desired_outputs:&Vec<f32>) {
-        let mut delta_matrix = vec![Vec::new(); self.layer_count];
-        delta_matrix[self.layer_count-1] = self.layers[self.layer_count-1].find_deltas(&values[self.layer_count-1], desired_outputs, &Layer::new(0, 0), Vec::new());
-        for num in (0..self.layer_count-1).rev() {
-            delta_matrix[num] = self.layers[num].find_deltas(&values[num], &Vec::new(), self.layers[num+1], &delta_matrix[num+1]);
-        }
+    pub fn find_cost(&self, inputs: &[f32], desired_outputs: &[f32]) -> f32 {
+    //Finds the cost function of one sample, which is the difference between the current result and the desired result.
+        let values = self.calculate(inputs);
+        self.cost.find(&values[values.len() - 1], desired_outputs)
+    }
 
+    #[cfg(feature = "std")]
+    pub fn predict_class(&self, inputs: &[f32]) -> (usize, Vec<f32>) {
+    //For classification: returns the position of the largest output along with all the outputs.
+    //With a Softmax module at the end, the outputs are the probabilities of each class.
         let values = self.calculate(inputs);
-        self.layers[0].alt_adjust(delta_matrix[0], inputs, self.learning_rate)
-        for num in 1..self.layer_count {
-            self.layers[num].alt_adjust(delta_matrix[num], values[num-1], self.learning_rate)
+        let probabilities = values[values.len() - 1].clone();
+        (dataset::decode_one_hot(&probabilities), probabilities)
+    }
+
+    pub fn find_make_adjust(&mut self, inputs: &[f32], desired_outputs: &[f32]) {
+    //Finds out how the weights and biases should be adjusted, based on the difference between the results of the calculate function and the desired outputs.
+    //It does it in reverse order, because that's how you have to do it.
+    //Then it implements all of the changes after they have all been calculated. That doesn't have to be done in reverse order, so it isn't.
+        self.find_adjusts(inputs, desired_outputs);
+        self.adjust();
+    }
+
+    pub fn find_adjusts(&mut self, inputs: &[f32], desired_outputs: &[f32]) -> Vec<f32> {
+    //The first half of find_make_adjust. Stores the adjusts in the modules without using them,
+    //and returns how the cost changes with each input, so whatever produced the inputs can be adjusted too.
+        let mut values = vec![inputs.to_vec()]; //Unlike in calculate, values[0] is the inputs, so values[num] are the inputs of layer num.
+        for num in 0..self.layer_count {
+            let next_values = self.layers[num].train_forward(&values[num], &mut self.rng);
+            values.push(next_values);
+        }
+        let outputs = &values[self.layer_count];
+        if outputs.len() != desired_outputs.len() {
+            panic!("The number of outputs ({}) doesn't match the number of desired outputs ({})", outputs.len(), desired_outputs.len());
+        }
+        let mut deltas = self.cost.deltas(outputs, desired_outputs);
+        for num in (0..self.layer_count).rev() {
+            deltas = self.layers[num].backward(&values[num], &values[num+1], &deltas);
+        }
+        deltas
+    }
+
+    pub fn find_deltas(&self, inputs: &[f32], desired_outputs: &[f32]) -> Vec<Vec<f32>> {
+    //Finds the delta of every node in the dense layers for one sample without adjusting anything, e.g. so they can be shown.
+    //The delta of a node is how the cost changes with its weighted sum, which is also its bias adjust. Other modules get an empty list.
+        let mut network = self.clone();
+        network.find_adjusts(inputs, desired_outputs);
+        let mut deltas = Vec::new();
+        for module in network.layers.iter() {
+            match module.as_layer() {
+                Some(layer) => deltas.push(layer.nodes.iter().map(|node| node.bias_adjust.unwrap_or(0.0)).collect()),
+                None => deltas.push(Vec::new()),
+            }
+        }
+        deltas
+    }
+
+    pub fn adjust(&mut self) {
+    //The second half of find_make_adjust. Changes the weights and biases using the adjusts stored by find_adjusts.
+        for num in 0..self.layer_count {
+            self.layers[num].adjust(self.learning_rate);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn default_rng() -> SimpleRng {
+    SimpleRng::new(rand::thread_rng().gen())
+}
+
+#[cfg(not(feature = "std"))]
+fn default_rng() -> SimpleRng {
+//Without std there is nothing random to seed from, so every network starts from the same seed. Change network.rng to use another one.
+    SimpleRng::new(0)
+}
+
+#[cfg(all(test, feature = "std"))]
+pub(crate) mod tests {
+    use super::*;
+
+    fn cost_weight(num: usize) -> f32 {
+    //Weights every output differently in the test cost, so that mixed up positions show up in the gradients.
+        (num % 7) as f32 * 0.3 - 0.8
+    }
+
+    fn weighted_cost(module: &dyn Module, inputs: &[f32]) -> f32 {
+        let outputs = module.forward(inputs);
+        let mut cost = 0.0;
+        for num in 0..outputs.len() {
+            cost += outputs[num] * cost_weight(num);
+        }
+        cost
+    }
+
+    pub(crate) fn check_gradients(module: &mut dyn Module, inputs: &[f32], tolerance: f32) {
+    //Compares the gradients and input deltas from backward with the change in the cost when each parameter and input is moved a little.
+        let step = 0.01;
+        let outputs = module.forward(inputs);
+        let output_deltas: Vec<f32> = (0..outputs.len()).map(cost_weight).collect();
+        let input_deltas = module.backward(inputs, &outputs, &output_deltas);
+        let gradients = module.gradients();
+        let parameters = module.parameters();
+        for num in 0..parameters.len() {
+            let mut moved = parameters.clone();
+            moved[num] = parameters[num] + step;
+            module.set_parameters(&moved);
+            let higher = weighted_cost(module, inputs);
+            moved[num] = parameters[num] - step;
+            module.set_parameters(&moved);
+            let lower = weighted_cost(module, inputs);
+            let expected = (higher - lower) / (2.0 * step);
+            assert!((gradients[num] - expected).abs() < tolerance, "parameter {}: {} vs {}", num, gradients[num], expected);
+        }
+        module.set_parameters(&parameters);
+        for num in 0..inputs.len() {
+            let mut moved = inputs.to_vec();
+            moved[num] = inputs[num] + step;
+            let higher = weighted_cost(module, &moved);
+            moved[num] = inputs[num] - step;
+            let lower = weighted_cost(module, &moved);
+            let expected = (higher - lower) / (2.0 * step);
+            assert!((input_deltas[num] - expected).abs() < tolerance, "input {}: {} vs {}", num, input_deltas[num], expected);
+        }
+    }
+
+    pub(crate) fn test_inputs(count: usize) -> Vec<f32> {
+        (0..count).map(|num| ((num * 5) % 11) as f32 * 0.2 - 1.0).collect()
+    }
+
+    #[test]
+    fn layer_gradients_match_finite_differences() {
+        let mut rng = SimpleRng::new(4);
+        for activation in [Activation::Sigmoid, Activation::Tanh, Activation::Identity].iter() {
+            let mut layer = Layer::from_rng(5, 3, *activation, &mut rng);
+            check_gradients(&mut layer, &test_inputs(5), 1e-2);
         }
     }
 }
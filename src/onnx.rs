@@ -0,0 +1,727 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::modules::Softmax;
+use crate::nodes_layers::{Activation, Module, Network};
+
+//Exports networks to ONNX, so they can be run by other programs, without depending on a protobuf library.
+//Each dense layer becomes a Gemm node (inputs times the transposed weights plus the biases), followed by its activation function.
+//Dropout is left out, since it does nothing outside training, and Flatten and Reshape are left out, since the values are already a flat list.
+//The graph has one input called "input" with the shape [N, inputs] and one output called "output" with the shape [N, outputs].
+//
+//There is also a small reader for the same kind of files, which is enough to check that an exported network gives the same outputs.
+
+const OPSET_VERSION: u64 = 13;
+const IR_VERSION: u64 = 7;
+const FLOAT: u64 = 1; //TensorProto.DataType.FLOAT
+const ATTRIBUTE_FLOAT: u64 = 1;
+const ATTRIBUTE_INT: u64 = 2;
+
+//Protobuf wire types
+const VARINT: u32 = 0;
+const FIXED64: u32 = 1;
+const LENGTH_DELIMITED: u32 = 2;
+const FIXED32: u32 = 5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Attribute {
+    Float(f32),
+    Int(i64),
+    Other, //Attributes of other types are kept by name, but their values aren't read.
+}
+
+#[derive(Clone, Debug)]
+pub struct OnnxNode {
+    pub op_type: String,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    pub attributes: Vec<(String, Attribute)>,
+}
+
+impl OnnxNode {
+    fn new(op_type: &str, inputs: Vec<String>, output: String) -> OnnxNode {
+        OnnxNode {
+            op_type: op_type.to_string(),
+            inputs,
+            outputs: vec![output],
+            attributes: Vec::new(),
+        }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes.iter().find(|(attribute_name, _)| attribute_name == name).map(|(_, attribute)| attribute)
+    }
+
+    fn int(&self, name: &str, default: i64) -> i64 {
+        match self.attribute(name) {
+            Some(Attribute::Int(value)) => *value,
+            _ => default,
+        }
+    }
+
+    fn float(&self, name: &str, default: f32) -> f32 {
+        match self.attribute(name) {
+            Some(Attribute::Float(value)) => *value,
+            _ => default,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tensor {
+    pub dimensions: Vec<usize>,
+    pub values: Vec<f32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OnnxModel {
+    pub nodes: Vec<OnnxNode>,
+    pub initializers: HashMap<String, Tensor>,
+    pub input: String,
+    pub output: String,
+}
+
+pub fn save_onnx(network: &Network, path: &Path) -> io::Result<()> {
+    fs::write(path, network_to_onnx(network)?)
+}
+
+pub fn network_to_onnx(network: &Network) -> io::Result<Vec<u8>> {
+//The bytes of an ONNX model of the network. Modules that can't be exported give an error.
+    let mut nodes: Vec<OnnxNode> = Vec::new();
+    let mut initializers: Vec<(String, Tensor)> = Vec::new();
+    let mut current = "input".to_string();
+    let mut input_size = None;
+    let mut output_size = None;
+    for (module_num, module) in network.layers.iter().enumerate() {
+        if let Some(layer) = module.as_layer() {
+            let previous_count = layer.nodes.first().map_or(0, |node| node.weights.len());
+            if let Err(error) = layer.check_shape(previous_count) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("The dense layer {} {}", module_num, error)))
+            }
+            if input_size.is_none() {
+                input_size = Some(previous_count);
+            }
+            output_size = Some(layer.node_count);
+            let mut weights = Vec::new();
+            for node in layer.nodes.iter() {
+                weights.extend_from_slice(&node.weights);
+            }
+            let weight_name = format!("layer{}_weights", module_num);
+            let bias_name = format!("layer{}_biases", module_num);
+            initializers.push((weight_name.clone(), Tensor {dimensions: vec![layer.node_count, previous_count], values: weights}));
+            initializers.push((bias_name.clone(), Tensor {dimensions: vec![layer.node_count], values: layer.nodes.iter().map(|node| node.bias).collect()}));
+            let mut gemm = OnnxNode::new("Gemm", vec![current.clone(), weight_name, bias_name], format!("layer{}_sum", module_num));
+            gemm.attributes.push(("transB".to_string(), Attribute::Int(1)));
+            current = gemm.outputs[0].clone();
+            nodes.push(gemm);
+            if let Some(node) = activation_node(&layer.activation, &current, format!("layer{}_values", module_num)) {
+                current = node.outputs[0].clone();
+                nodes.push(node);
+            }
+        } else if let Some(activation_layer) = module.as_activation_layer() {
+            if let Some(node) = activation_node(&activation_layer.activation, &current, format!("module{}_values", module_num)) {
+                current = node.outputs[0].clone();
+                nodes.push(node);
+            }
+        } else if module.as_softmax().is_some() {
+            let mut node = OnnxNode::new("Softmax", vec![current.clone()], format!("module{}_values", module_num));
+            node.attributes.push(("axis".to_string(), Attribute::Int(1)));
+            current = node.outputs[0].clone();
+            nodes.push(node);
+        } else if !module.passes_values_on() {
+            return Err(unsupported(&module.describe()))
+        }
+    }
+    let (input_size, output_size) = match (input_size, output_size) {
+        (Some(input_size), Some(output_size)) => (input_size, output_size),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "The network needs at least one dense layer to know its number of inputs")),
+    };
+    match nodes.last_mut() {
+        Some(node) => node.outputs[0] = "output".to_string(),
+        None => nodes.push(OnnxNode::new("Identity", vec![current], "output".to_string())),
+    }
+
+    let mut graph = Vec::new();
+    for node in nodes.iter() {
+        field_bytes(&mut graph, 1, &encode_node(node));
+    }
+    field_bytes(&mut graph, 2, b"simple_nn");
+    for (name, tensor) in initializers.iter() {
+        field_bytes(&mut graph, 5, &encode_tensor(name, tensor));
+    }
+    field_bytes(&mut graph, 11, &encode_value_info("input", input_size));
+    field_bytes(&mut graph, 12, &encode_value_info("output", output_size));
+
+    let mut opset = Vec::new();
+    field_bytes(&mut opset, 1, b"");
+    field_varint(&mut opset, 2, OPSET_VERSION);
+
+    let mut model = Vec::new();
+    field_varint(&mut model, 1, IR_VERSION);
+    field_bytes(&mut model, 2, b"simple_nn");
+    field_bytes(&mut model, 3, env!("CARGO_PKG_VERSION").as_bytes());
+    field_bytes(&mut model, 7, &graph);
+    field_bytes(&mut model, 8, &opset);
+    Ok(model)
+}
+
+fn activation_node(activation: &Activation, input: &str, output: String) -> Option<OnnxNode> {
+    let op_type = match activation {
+        Activation::Sigmoid => "Sigmoid",
+        Activation::Tanh => "Tanh",
+        Activation::Relu => "Relu",
+        Activation::Identity => return None,
+    };
+    Some(OnnxNode::new(op_type, vec![input.to_string()], output))
+}
+
+fn unsupported(description: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("The module {} can't be exported to ONNX", description))
+}
+
+fn encode_node(node: &OnnxNode) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for input in node.inputs.iter() {
+        field_bytes(&mut bytes, 1, input.as_bytes());
+    }
+    for output in node.outputs.iter() {
+        field_bytes(&mut bytes, 2, output.as_bytes());
+    }
+    field_bytes(&mut bytes, 3, node.outputs[0].as_bytes()); // The node is named after its output
+    field_bytes(&mut bytes, 4, node.op_type.as_bytes());
+    for (name, attribute) in node.attributes.iter() {
+        let mut attribute_bytes = Vec::new();
+        field_bytes(&mut attribute_bytes, 1, name.as_bytes());
+        match attribute {
+            Attribute::Float(value) => {
+                field_fixed32(&mut attribute_bytes, 2, value.to_bits());
+                field_varint(&mut attribute_bytes, 20, ATTRIBUTE_FLOAT);
+            },
+            Attribute::Int(value) => {
+                field_varint(&mut attribute_bytes, 3, *value as u64);
+                field_varint(&mut attribute_bytes, 20, ATTRIBUTE_INT);
+            },
+            Attribute::Other => panic!("Only float and int attributes can be written"),
+        }
+        field_bytes(&mut bytes, 5, &attribute_bytes);
+    }
+    bytes
+}
+
+fn encode_tensor(name: &str, tensor: &Tensor) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for dimension in tensor.dimensions.iter() {
+        field_varint(&mut bytes, 1, *dimension as u64);
+    }
+    field_varint(&mut bytes, 2, FLOAT);
+    field_bytes(&mut bytes, 8, name.as_bytes());
+    let mut raw = Vec::new();
+    for value in tensor.values.iter() {
+        raw.extend_from_slice(&value.to_le_bytes());
+    }
+    field_bytes(&mut bytes, 9, &raw);
+    bytes
+}
+
+fn encode_value_info(name: &str, size: usize) -> Vec<u8> {
+//A float tensor with the shape [N, size], where N is the number of samples.
+    let mut batch = Vec::new();
+    field_bytes(&mut batch, 2, b"N");
+    let mut features = Vec::new();
+    field_varint(&mut features, 1, size as u64);
+    let mut shape = Vec::new();
+    field_bytes(&mut shape, 1, &batch);
+    field_bytes(&mut shape, 1, &features);
+    let mut tensor_type = Vec::new();
+    field_varint(&mut tensor_type, 1, FLOAT);
+    field_bytes(&mut tensor_type, 2, &shape);
+    let mut type_proto = Vec::new();
+    field_bytes(&mut type_proto, 1, &tensor_type);
+    let mut bytes = Vec::new();
+    field_bytes(&mut bytes, 1, name.as_bytes());
+    field_bytes(&mut bytes, 2, &type_proto);
+    bytes
+}
+
+fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn push_key(bytes: &mut Vec<u8>, field: u32, wire_type: u32) {
+    push_varint(bytes, ((field << 3) | wire_type) as u64);
+}
+
+fn field_varint(bytes: &mut Vec<u8>, field: u32, value: u64) {
+    push_key(bytes, field, VARINT);
+    push_varint(bytes, value);
+}
+
+fn field_fixed32(bytes: &mut Vec<u8>, field: u32, value: u32) {
+    push_key(bytes, field, FIXED32);
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn field_bytes(bytes: &mut Vec<u8>, field: u32, data: &[u8]) {
+    push_key(bytes, field, LENGTH_DELIMITED);
+    push_varint(bytes, data.len() as u64);
+    bytes.extend_from_slice(data);
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+    Fixed64, //No field the reader uses is a double, so the value is skipped.
+}
+
+fn read_fields(bytes: &[u8]) -> io::Result<Vec<(u32, Value<'_>)>> {
+//Splits a protobuf message into its fields, in the order they were written.
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while position < bytes.len() {
+        let key = read_varint(bytes, &mut position)?;
+        let field = (key >> 3) as u32;
+        let value = match (key & 7) as u32 {
+            VARINT => Value::Varint(read_varint(bytes, &mut position)?),
+            FIXED64 => {
+                take(bytes, &mut position, 8)?;
+                Value::Fixed64
+            },
+            LENGTH_DELIMITED => {
+                let length = read_varint(bytes, &mut position)? as usize;
+                Value::Bytes(take(bytes, &mut position, length)?)
+            },
+            FIXED32 => {
+                let data = take(bytes, &mut position, 4)?;
+                Value::Fixed32(u32::from_le_bytes([data[0], data[1], data[2], data[3]]))
+            },
+            other => return Err(invalid(format!("Unknown protobuf wire type {}", other))),
+        };
+        fields.push((field, value));
+    }
+    Ok(fields)
+}
+
+fn read_varint(bytes: &[u8], position: &mut usize) -> io::Result<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        if *position >= bytes.len() || shift > 63 {
+            return Err(invalid("A protobuf number ended too early".to_string()))
+        }
+        let byte = bytes[*position];
+        *position += 1;
+        value |= ((byte & 0x7F) as u64) << shift;
+        if byte < 0x80 {
+            return Ok(value)
+        }
+        shift += 7;
+    }
+}
+
+fn take<'a>(bytes: &'a [u8], position: &mut usize, length: usize) -> io::Result<&'a [u8]> {
+    let end = match position.checked_add(length) {
+        Some(end) if end <= bytes.len() => end,
+        _ => return Err(invalid("A protobuf field ended too early".to_string())),
+    };
+    let data = &bytes[*position..end];
+    *position = end;
+    Ok(data)
+}
+
+fn text(data: &[u8]) -> io::Result<String> {
+    match String::from_utf8(data.to_vec()) {
+        Ok(text) => Ok(text),
+        Err(_) => Err(invalid("A protobuf string isn't valid UTF-8".to_string())),
+    }
+}
+
+fn packed_or_single(value: &Value, values: &mut Vec<u64>) -> io::Result<()> {
+//Repeated numbers can be written one per field or packed together in one field.
+    match value {
+        Value::Varint(number) => values.push(*number),
+        Value::Bytes(data) => {
+            let mut position = 0;
+            while position < data.len() {
+                values.push(read_varint(data, &mut position)?);
+            }
+        },
+        _ => return Err(invalid("Expected a list of numbers".to_string())),
+    }
+    Ok(())
+}
+
+pub fn load_onnx(path: &Path) -> io::Result<OnnxModel> {
+    read_onnx(&fs::read(path)?)
+}
+
+pub fn read_onnx(bytes: &[u8]) -> io::Result<OnnxModel> {
+//Reads the graph of an ONNX model: its nodes, its float initializers, and the names of its first input and output.
+    let mut graph = None;
+    for (field, value) in read_fields(bytes)? {
+        if let (7, Value::Bytes(data)) = (field, value) {
+            graph = Some(data);
+        }
+    }
+    let graph = match graph {
+        Some(graph) => graph,
+        None => return Err(invalid("The ONNX model has no graph".to_string())),
+    };
+    let mut model = OnnxModel {
+        nodes: Vec::new(),
+        initializers: HashMap::new(),
+        input: String::new(),
+        output: String::new(),
+    };
+    let mut inputs = Vec::new();
+    for (field, value) in read_fields(graph)? {
+        let data = match value {
+            Value::Bytes(data) => data,
+            _ => continue,
+        };
+        match field {
+            1 => model.nodes.push(read_node(data)?),
+            5 => {
+                let (name, tensor) = read_tensor(data)?;
+                model.initializers.insert(name, tensor);
+            },
+            11 => inputs.push(read_name(data)?),
+            12 if model.output.is_empty() => model.output = read_name(data)?,
+            _ => (),
+        }
+    }
+    //Older exporters list the initializers as inputs as well, so the real input is the first one that isn't an initializer.
+    match inputs.iter().find(|name| !model.initializers.contains_key(*name)) {
+        Some(input) => model.input = input.clone(),
+        None => return Err(invalid("The ONNX graph has no input".to_string())),
+    }
+    if model.output.is_empty() {
+        return Err(invalid("The ONNX graph has no output".to_string()))
+    }
+    Ok(model)
+}
+
+fn read_name(data: &[u8]) -> io::Result<String> {
+    for (field, value) in read_fields(data)? {
+        if let (1, Value::Bytes(name)) = (field, value) {
+            return text(name)
+        }
+    }
+    Err(invalid("A value in the ONNX graph has no name".to_string()))
+}
+
+fn read_node(data: &[u8]) -> io::Result<OnnxNode> {
+    let mut node = OnnxNode {
+        op_type: String::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+        attributes: Vec::new(),
+    };
+    for (field, value) in read_fields(data)? {
+        match (field, value) {
+            (1, Value::Bytes(input)) => node.inputs.push(text(input)?),
+            (2, Value::Bytes(output)) => node.outputs.push(text(output)?),
+            (4, Value::Bytes(op_type)) => node.op_type = text(op_type)?,
+            (5, Value::Bytes(attribute)) => node.attributes.push(read_attribute(attribute)?),
+            _ => (),
+        }
+    }
+    //OnnxModel::run uses the first input and output of every node, and the second input of the operators that combine two values.
+    let needed_inputs = match node.op_type.as_str() {
+        "Gemm" | "MatMul" | "Add" => 2,
+        _ => 1,
+    };
+    if node.inputs.len() < needed_inputs || node.outputs.is_empty() {
+        return Err(invalid(format!("The {} node has {} inputs and {} outputs, but needs at least {} and 1", node.op_type, node.inputs.len(), node.outputs.len(), needed_inputs)))
+    }
+    Ok(node)
+}
+
+fn read_attribute(data: &[u8]) -> io::Result<(String, Attribute)> {
+    let mut name = String::new();
+    let mut float = None;
+    let mut int = None;
+    let mut attribute_type = 0;
+    for (field, value) in read_fields(data)? {
+        match (field, value) {
+            (1, Value::Bytes(text_data)) => name = text(text_data)?,
+            (2, Value::Fixed32(bits)) => float = Some(f32::from_bits(bits)),
+            (3, Value::Varint(number)) => int = Some(number as i64),
+            (20, Value::Varint(number)) => attribute_type = number,
+            _ => (),
+        }
+    }
+    let attribute = match (attribute_type, float, int) {
+        (ATTRIBUTE_FLOAT, Some(value), _) => Attribute::Float(value),
+        (ATTRIBUTE_INT, _, Some(value)) => Attribute::Int(value),
+        (0, Some(value), None) => Attribute::Float(value), // Files from before the type was written
+        (0, None, Some(value)) => Attribute::Int(value),
+        _ => Attribute::Other,
+    };
+    Ok((name, attribute))
+}
+
+fn read_tensor(data: &[u8]) -> io::Result<(String, Tensor)> {
+    let mut name = String::new();
+    let mut dimensions = Vec::new();
+    let mut data_type = 0;
+    let mut values = Vec::new();
+    let mut packed = Vec::new();
+    let mut raw = None;
+    for (field, value) in read_fields(data)? {
+        match field {
+            1 => packed_or_single(&value, &mut dimensions)?,
+            2 => if let Value::Varint(number) = value {
+                data_type = number;
+            },
+            4 => match value { // float_data, packed or one at a time
+                Value::Fixed32(bits) => values.push(f32::from_bits(bits)),
+                Value::Bytes(packed_data) => packed.push(packed_data),
+                _ => (),
+            },
+            8 => if let Value::Bytes(text_data) = value {
+                name = text(text_data)?;
+            },
+            9 => if let Value::Bytes(raw_data) = value {
+                raw = Some(raw_data);
+            },
+            _ => (),
+        }
+    }
+    if data_type != FLOAT {
+        return Err(invalid(format!("The tensor {} isn't made of 32 bit floats", name)))
+    }
+    for packed_data in packed.iter() {
+        values.extend(read_floats(packed_data, &name)?);
+    }
+    if let Some(raw) = raw {
+        values = read_floats(raw, &name)?;
+    }
+    let dimensions: Vec<usize> = dimensions.iter().map(|dimension| *dimension as usize).collect();
+    let size = dimensions.iter().try_fold(1usize, |size, dimension| size.checked_mul(*dimension));
+    if size != Some(values.len()) {
+        return Err(invalid(format!("The tensor {} has {} values, which doesn't match its shape {:?}", name, values.len(), dimensions)))
+    }
+    Ok((name, Tensor {
+        dimensions,
+        values,
+    }))
+}
+
+fn read_floats(data: &[u8], name: &str) -> io::Result<Vec<f32>> {
+//Little-endian 32 bit floats, packed together without gaps.
+    let chunks = data.chunks_exact(4);
+    if !chunks.remainder().is_empty() {
+        return Err(invalid(format!("The data of the tensor {} is {} bytes long, which isn't a whole number of floats", name, data.len())))
+    }
+    Ok(chunks.map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])).collect())
+}
+
+impl OnnxModel {
+    pub fn run(&self, inputs: &[f32]) -> io::Result<Vec<f32>> {
+    //Runs the graph on one sample. Only the operators the exporter uses, and MatMul and Add, are supported.
+        let mut values: HashMap<String, Vec<f32>> = HashMap::new();
+        values.insert(self.input.clone(), inputs.to_vec());
+        let get = |values: &HashMap<String, Vec<f32>>, name: &str| -> io::Result<Tensor> {
+            if let Some(tensor) = self.initializers.get(name) {
+                return Ok(tensor.clone())
+            }
+            match values.get(name) {
+                Some(row) => Ok(Tensor {dimensions: vec![1, row.len()], values: row.clone()}),
+                None => Err(invalid(format!("The value {} is used before it is calculated", name))),
+            }
+        };
+        for node in self.nodes.iter() {
+            let input = get(&values, &node.inputs[0])?;
+            let result = match node.op_type.as_str() {
+                "Gemm" => {
+                    if node.int("transA", 0) != 0 {
+                        return Err(invalid("Gemm with transA isn't supported".to_string()))
+                    }
+                    let weights = get(&values, &node.inputs[1])?;
+                    let mut result = multiply(&input.values, &weights, node.int("transB", 0) != 0)?;
+                    let alpha = node.float("alpha", 1.0);
+                    let beta = node.float("beta", 1.0);
+                    let biases = if node.inputs.len() > 2 {Some(get(&values, &node.inputs[2])?)} else {None};
+                    if let Some(biases) = &biases {
+                        if biases.values.len() != result.len() && biases.values.len() != 1 {
+                            return Err(invalid(format!("Can't add {} biases to {} values", biases.values.len(), result.len())))
+                        }
+                    }
+                    for num in 0..result.len() {
+                        result[num] *= alpha;
+                        if let Some(biases) = &biases {
+                            result[num] += beta * biases.values[if biases.values.len() == 1 {0} else {num}];
+                        }
+                    }
+                    result
+                },
+                "MatMul" => multiply(&input.values, &get(&values, &node.inputs[1])?, false)?,
+                "Add" => {
+                    let other = get(&values, &node.inputs[1])?;
+                    if other.values.len() != input.values.len() && other.values.len() != 1 {
+                        return Err(invalid(format!("Can't add {} values to {} values", other.values.len(), input.values.len())))
+                    }
+                    (0..input.values.len()).map(|num| input.values[num] + other.values[if other.values.len() == 1 {0} else {num}]).collect()
+                },
+                "Sigmoid" => input.values.iter().map(|value| Activation::Sigmoid.apply(*value)).collect(),
+                "Tanh" => input.values.iter().map(|value| Activation::Tanh.apply(*value)).collect(),
+                "Relu" => input.values.iter().map(|value| Activation::Relu.apply(*value)).collect(),
+                "Softmax" => Softmax::new().forward(&input.values),
+                "Identity" | "Dropout" | "Flatten" => input.values.clone(),
+                other => return Err(invalid(format!("The ONNX operator {} isn't supported", other))),
+            };
+            values.insert(node.outputs[0].clone(), result);
+        }
+        match values.remove(&self.output) {
+            Some(outputs) => Ok(outputs),
+            None => Err(invalid(format!("The output {} is never calculated", self.output))),
+        }
+    }
+}
+
+fn multiply(row: &[f32], matrix: &Tensor, transposed: bool) -> io::Result<Vec<f32>> {
+//The row times the matrix, or times the transposed matrix.
+    if matrix.dimensions.len() != 2 {
+        return Err(invalid(format!("Expected a matrix, but the shape is {:?}", matrix.dimensions)))
+    }
+    let (rows, columns) = if transposed {(matrix.dimensions[1], matrix.dimensions[0])} else {(matrix.dimensions[0], matrix.dimensions[1])};
+    if rows != row.len() {
+        return Err(invalid(format!("Can't multiply {} values with a {}x{} matrix", row.len(), rows, columns)))
+    }
+    let mut result = vec![0.0; columns];
+    for column in 0..columns {
+        for num in 0..rows {
+            let weight = if transposed {matrix.values[column * rows + num]} else {matrix.values[num * columns + column]};
+            result[column] += row[num] * weight;
+        }
+    }
+    Ok(result)
+}
+
+pub fn check_round_trip(network: &Network, samples: &[Vec<f32>]) -> io::Result<f32> {
+//Exports the network, reads the export back in, and returns the largest difference between their outputs for the samples.
+    let model = read_onnx(&network_to_onnx(network)?)?;
+    let mut largest = 0.0f32;
+    for inputs in samples.iter() {
+        let values = network.calculate(inputs);
+        let expected = &values[values.len() - 1];
+        let outputs = model.run(inputs)?;
+        if outputs.len() != expected.len() {
+            return Err(invalid(format!("The export gives {} outputs instead of {}", outputs.len(), expected.len())))
+        }
+        for num in 0..outputs.len() {
+            largest = largest.max((outputs[num] - expected[num]).abs());
+        }
+    }
+    Ok(largest)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::ActivationLayer;
+    use crate::nodes_layers::Layer;
+    use crate::rng::SimpleRng;
+
+    #[test]
+    fn exported_network_gives_the_same_outputs() {
+        let mut rng = SimpleRng::new(3);
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer::from_rng(4, 8, Activation::Relu, &mut rng)));
+        network.push(Box::new(Layer::from_rng(8, 6, Activation::Identity, &mut rng)));
+        network.push(Box::new(ActivationLayer::new(Activation::Tanh)));
+        network.push(Box::new(Layer::from_rng(6, 3, Activation::Sigmoid, &mut rng)));
+        network.push(Box::new(Softmax::new()));
+        let model = read_onnx(&network_to_onnx(&network).unwrap()).unwrap();
+        for _ in 0..20 {
+            let inputs: Vec<f32> = (0..4).map(|_| rng.gen_range(-2.0, 2.0)).collect();
+            let values = network.calculate(&inputs);
+            let expected = &values[values.len() - 1];
+            let outputs = model.run(&inputs).unwrap();
+            assert_eq!(outputs.len(), expected.len());
+            for num in 0..outputs.len() {
+                //The export adds the bias after the weighted sum instead of before, so the last bit can differ.
+                assert!((outputs[num] - expected[num]).abs() < 1e-6, "{:?} != {:?}", outputs, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn layer_without_nodes_is_an_error() {
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer {
+            nodes: Vec::new(),
+            node_count: 0,
+            activation: Activation::Sigmoid,
+        }));
+        assert!(network_to_onnx(&network).is_err());
+    }
+
+    #[test]
+    fn truncated_raw_data_is_an_error() {
+        let mut tensor = Vec::new();
+        field_varint(&mut tensor, 1, 2);
+        field_varint(&mut tensor, 2, FLOAT);
+        field_bytes(&mut tensor, 8, b"weights");
+        field_bytes(&mut tensor, 9, &[0; 7]);
+        assert!(read_tensor(&tensor).is_err());
+    }
+
+    #[test]
+    fn nodes_without_their_inputs_are_errors() {
+        let gemm = OnnxNode::new("Gemm", vec!["input".to_string()], "sum".to_string());
+        assert!(read_node(&encode_node(&gemm)).is_err());
+        let relu = OnnxNode::new("Relu", vec!["sum".to_string()], "values".to_string());
+        assert!(read_node(&encode_node(&relu)).is_ok());
+        let mut without_output = Vec::new();
+        field_bytes(&mut without_output, 1, b"sum");
+        field_bytes(&mut without_output, 4, b"Relu");
+        assert!(read_node(&without_output).is_err());
+        let identity = OnnxNode::new("Identity", Vec::new(), "output".to_string());
+        assert!(read_node(&encode_node(&identity)).is_err());
+    }
+
+    #[test]
+    fn biases_of_the_wrong_length_are_errors() {
+        let mut initializers = HashMap::new();
+        initializers.insert("weights".to_string(), Tensor {dimensions: vec![2, 2], values: vec![1.0, 0.0, 0.0, 1.0]});
+        initializers.insert("biases".to_string(), Tensor {dimensions: vec![3], values: vec![0.5, 0.5, 0.5]});
+        let gemm = OnnxNode::new("Gemm", vec!["input".to_string(), "weights".to_string(), "biases".to_string()], "output".to_string());
+        let mut model = OnnxModel {
+            nodes: vec![gemm],
+            initializers,
+            input: "input".to_string(),
+            output: "output".to_string(),
+        };
+        assert!(model.run(&[1.0, 2.0]).is_err());
+        model.initializers.insert("biases".to_string(), Tensor {dimensions: vec![1], values: vec![0.5]});
+        assert_eq!(model.run(&[1.0, 2.0]).unwrap(), vec![1.5, 2.5]);
+    }
+
+    #[test]
+    fn lengths_that_overflow_are_errors() {
+        let mut bytes = vec![(1 << 3) | LENGTH_DELIMITED as u8];
+        bytes.extend_from_slice(&[0xFF; 9]);
+        bytes.push(0x01); // The length is the largest 64 bit number
+        bytes.push(0);
+        assert!(read_fields(&bytes).is_err());
+
+        let mut tensor = Vec::new();
+        field_varint(&mut tensor, 1, 1 << 40);
+        field_varint(&mut tensor, 1, 1 << 40);
+        field_varint(&mut tensor, 2, FLOAT);
+        field_bytes(&mut tensor, 8, b"weights");
+        assert!(read_tensor(&tensor).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::checkpoint::{invalid, network_to_text, parse, read_network, Lines};
+use crate::dataset::Dataset;
+use crate::nodes_layers::Network;
+
+//Turns raw input columns into values a network can use, so large numbers don't saturate the sigmoid.
+//A Preprocessor is fitted on the training data only, and the same fitted values are then used for every input,
+//including at inference after the model has been saved and loaded. Missing values are written as NaN.
+
+const MODEL_HEADER: &str = "simple_nn model 1";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scaling {
+    None,
+    MinMax, //From the smallest and largest value seen to [0; 1]
+    ZScore, //Subtracts the mean and divides by the standard deviation.
+    OneHot, //The value is a category number, which becomes a one-hot list with a place for each category seen.
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Imputation {
+//What missing values are replaced with, found from the training data.
+    Mean,
+    Median,
+    MostFrequent,
+    Constant(f32),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Column {
+//How a column should be preprocessed.
+    pub scaling: Scaling,
+    pub imputation: Imputation,
+}
+
+impl Column {
+    pub fn new(scaling: Scaling) -> Column {
+    //Missing numbers are replaced with the mean, and missing categories with the most frequent one.
+        let imputation = if scaling == Scaling::OneHot {Imputation::MostFrequent} else {Imputation::Mean};
+        Column {
+            scaling,
+            imputation,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FittedColumn {
+    pub scaling: Scaling,
+    pub fill: f32, //Used instead of missing values.
+    pub parameters: Vec<f32>, //The smallest and largest value for MinMax, the mean and standard deviation for ZScore, and the categories for OneHot.
+}
+
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    pub columns: Vec<FittedColumn>,
+}
+
+impl Preprocessor {
+    pub fn fit(inputs: &[Vec<f32>], columns: &[Column]) -> Preprocessor {
+    //Finds the values needed to preprocess each column, ignoring missing values.
+        let mut fitted = Vec::new();
+        for (column_num, column) in columns.iter().enumerate() {
+            let mut values = Vec::new();
+            for row in inputs.iter() {
+                if row.len() != columns.len() {
+                    panic!("A row has {} values, but there are {} columns", row.len(), columns.len());
+                }
+                if !row[column_num].is_nan() {
+                    values.push(row[column_num]);
+                }
+            }
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let fill = match column.imputation {
+                Imputation::Mean => mean(&values),
+                Imputation::Median => median(&values),
+                Imputation::MostFrequent => most_frequent(&values),
+                Imputation::Constant(value) => value,
+            };
+            let parameters = match column.scaling {
+                Scaling::None => Vec::new(),
+                Scaling::MinMax => if values.is_empty() {vec![0.0, 1.0]} else {vec![values[0], values[values.len() - 1]]},
+                Scaling::ZScore => {
+                    let mean = mean(&values);
+                    let variance = values.iter().map(|value| (value - mean) * (value - mean)).sum::<f32>() / values.len().max(1) as f32;
+                    vec![mean, variance.sqrt()]
+                },
+                Scaling::OneHot => {
+                    let mut categories = values.clone();
+                    categories.dedup();
+                    categories
+                },
+            };
+            fitted.push(FittedColumn {
+                scaling: column.scaling,
+                fill,
+                parameters,
+            });
+        }
+        Preprocessor {
+            columns: fitted,
+        }
+    }
+
+    pub fn output_size(&self) -> usize {
+    //The number of values transform gives, which is the number of inputs the network needs.
+        self.columns.iter().map(|column| if column.scaling == Scaling::OneHot {column.parameters.len()} else {1}).sum()
+    }
+
+    pub fn transform(&self, inputs: &[f32]) -> Vec<f32> {
+        if inputs.len() != self.columns.len() {
+            panic!("The number of inputs ({}) doesn't match the number of columns ({})", inputs.len(), self.columns.len());
+        }
+        let mut outputs = Vec::new();
+        for (column, input) in self.columns.iter().zip(inputs.iter()) {
+            let value = if input.is_nan() {column.fill} else {*input};
+            match column.scaling {
+                Scaling::None => outputs.push(value),
+                Scaling::MinMax => {
+                    let range = column.parameters[1] - column.parameters[0];
+                    outputs.push(if range > 0.0 {(value - column.parameters[0]) / range} else {0.0});
+                },
+                Scaling::ZScore => {
+                    let deviation = if column.parameters[1] > 0.0 {column.parameters[1]} else {1.0};
+                    outputs.push((value - column.parameters[0]) / deviation);
+                },
+                Scaling::OneHot => { // A category that wasn't seen while fitting gives all zeros
+                    for category in column.parameters.iter() {
+                        outputs.push(if *category == value {1.0} else {0.0});
+                    }
+                },
+            }
+        }
+        outputs
+    }
+
+    pub fn transform_dataset(&self, data: &Dataset) -> Dataset {
+        Dataset::new(data.inputs.iter().map(|inputs| self.transform(inputs)).collect(), data.outputs.clone())
+    }
+
+    pub fn to_text(&self) -> String {
+    //One line per column: "column <scaling> <fill> <parameters>", after a line with the number of columns.
+        let mut text = format!("preprocessor {}\n", self.columns.len());
+        for column in self.columns.iter() {
+            let scaling = match column.scaling {
+                Scaling::None => "none",
+                Scaling::MinMax => "min_max",
+                Scaling::ZScore => "z_score",
+                Scaling::OneHot => "one_hot",
+            };
+            let mut line = format!("column {} {}", scaling, column.fill);
+            for parameter in column.parameters.iter() {
+                line.push_str(&format!(" {}", parameter));
+            }
+            text.push_str(&line);
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn read(lines: &mut Lines) -> io::Result<Preprocessor> {
+    //Reads the lines written by to_text.
+        let column_count: usize = parse(&lines.value("preprocessor")?)?;
+        let mut columns = Vec::new();
+        for _ in 0..column_count {
+            let line = lines.value("column")?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if words.len() < 2 {
+                return Err(invalid(format!("The column {:?} needs a scaling and a fill value", line)))
+            }
+            let scaling = match words[0] {
+                "none" => Scaling::None,
+                "min_max" => Scaling::MinMax,
+                "z_score" => Scaling::ZScore,
+                "one_hot" => Scaling::OneHot,
+                other => return Err(invalid(format!("Unknown scaling {}", other))),
+            };
+            let mut parameters = Vec::new();
+            for word in words[2..].iter() {
+                parameters.push(parse(word)?);
+            }
+            let needed = match scaling {
+                Scaling::None => Some(0),
+                Scaling::MinMax | Scaling::ZScore => Some(2),
+                Scaling::OneHot => None,
+            };
+            if needed.is_some() && needed != Some(parameters.len()) {
+                return Err(invalid(format!("The column {:?} has the wrong number of values", line)))
+            }
+            columns.push(FittedColumn {
+                scaling,
+                fill: parse(words[1])?,
+                parameters,
+            });
+        }
+        Ok(Preprocessor {
+            columns,
+        })
+    }
+}
+
+pub fn save_model(preprocessor: &Preprocessor, network: &Network, path: &Path) -> io::Result<()> {
+//Saves a network together with the preprocessor its inputs go through, so they can't be separated by mistake.
+    fs::write(path, format!("{}\n{}{}", MODEL_HEADER, preprocessor.to_text(), network_to_text(network)))
+}
+
+pub fn load_model(path: &Path) -> io::Result<(Preprocessor, Network)> {
+    let text = fs::read_to_string(path)?;
+    let mut lines = Lines::new(&text);
+    if lines.next_line()? != MODEL_HEADER {
+        return Err(invalid(format!("{} isn't a saved model", path.display())))
+    }
+    let preprocessor = Preprocessor::read(&mut lines)?;
+    let network = read_network(&mut lines)?;
+    Ok((preprocessor, network))
+}
+
+fn mean(values: &[f32]) -> f32 {
+    if values.is_empty() {
+        return 0.0
+    }
+    values.iter().sum::<f32>() / values.len() as f32
+}
+
+fn median(sorted_values: &[f32]) -> f32 {
+//The middle value, or the mean of the two middle values if there is an even number of them.
+    let count = sorted_values.len();
+    if count == 0 {
+        return 0.0
+    }
+    if count % 2 == 1 {
+        sorted_values[count / 2]
+    } else {
+        (sorted_values[count / 2 - 1] + sorted_values[count / 2]) / 2.0
+    }
+}
+
+fn most_frequent(sorted_values: &[f32]) -> f32 {
+//The value that appears the most times. Ties go to the smallest value.
+    let mut best = 0.0;
+    let mut best_count = 0;
+    let mut num = 0;
+    while num < sorted_values.len() {
+        let mut count = 1;
+        while num + count < sorted_values.len() && sorted_values[num + count] == sorted_values[num] {
+            count += 1;
+        }
+        if count > best_count {
+            best = sorted_values[num];
+            best_count = count;
+        }
+        num += count;
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn houses() -> Vec<Vec<f32>> {
+    //Area, number of rooms and a category, with some of them missing.
+        vec![
+            vec![120.0, 3.0, 2.0],
+            vec![80.0, f32::NAN, 0.0],
+            vec![f32::NAN, 2.0, 2.0],
+            vec![200.0, 5.0, f32::NAN],
+            vec![40.0, 1.0, 1.0],
+        ]
+    }
+
+    #[test]
+    fn fills_missing_values() {
+        let inputs = houses();
+        let columns = [Column::new(Scaling::None), Column {scaling: Scaling::None, imputation: Imputation::Median}, Column::new(Scaling::OneHot)];
+        let preprocessor = Preprocessor::fit(&inputs, &columns);
+        assert_eq!(preprocessor.columns[0].fill, 110.0); // The mean of 40, 80, 120 and 200
+        assert_eq!(preprocessor.columns[1].fill, 2.5); // Between 2 and 3, the middle of 1, 2, 3 and 5
+        assert_eq!(preprocessor.columns[2].fill, 2.0); // The most frequent category
+        assert_eq!(preprocessor.transform(&inputs[2]), vec![110.0, 2.0, 0.0, 0.0, 1.0]);
+        assert_eq!(preprocessor.transform(&inputs[1]), vec![80.0, 2.5, 1.0, 0.0, 0.0]);
+
+        let odd = Preprocessor::fit(&[vec![3.0], vec![1.0], vec![f32::NAN], vec![7.0]], &[Column {scaling: Scaling::None, imputation: Imputation::Median}]);
+        assert_eq!(odd.columns[0].fill, 3.0);
+        let constant = Preprocessor::fit(&inputs, &[Column::new(Scaling::None), Column {scaling: Scaling::None, imputation: Imputation::Constant(-1.0)}, Column::new(Scaling::None)]);
+        assert_eq!(constant.transform(&inputs[1])[1], -1.0);
+    }
+
+    #[test]
+    fn scales_columns() {
+        let inputs = houses();
+        let columns = [Column::new(Scaling::MinMax), Column::new(Scaling::ZScore), Column::new(Scaling::OneHot)];
+        let preprocessor = Preprocessor::fit(&inputs, &columns);
+        assert_eq!(preprocessor.output_size(), 5);
+        assert_eq!(preprocessor.columns[0].parameters, vec![40.0, 200.0]);
+        assert_eq!(preprocessor.columns[2].parameters, vec![0.0, 1.0, 2.0]);
+        let outputs = preprocessor.transform(&inputs[0]);
+        assert_eq!(outputs[0], 0.5);
+        assert!((outputs[1] - (3.0 - 2.75) / preprocessor.columns[1].parameters[1]).abs() < 1e-6);
+        assert_eq!(outputs[2..].to_vec(), vec![0.0, 0.0, 1.0]);
+        assert_eq!(preprocessor.transform(&[40.0, 2.75, 9.0])[2..].to_vec(), vec![0.0, 0.0, 0.0]); // A category that wasn't seen
+    }
+
+    #[test]
+    fn saved_models_load_the_same() {
+        let inputs = houses();
+        let columns = [Column::new(Scaling::MinMax), Column {scaling: Scaling::ZScore, imputation: Imputation::Median}, Column::new(Scaling::OneHot)];
+        let preprocessor = Preprocessor::fit(&inputs, &columns);
+        let network = Network::with_seed(vec![preprocessor.output_size(), 4, 1], 0.5, 5);
+        let path = std::env::temp_dir().join(format!("simple_nn_model_test_{}.txt", std::process::id()));
+        save_model(&preprocessor, &network, &path).unwrap();
+        let loaded = load_model(&path);
+        fs::remove_file(&path).unwrap();
+        let (loaded_preprocessor, loaded_network) = loaded.unwrap();
+        for row in inputs.iter() {
+            let transformed = preprocessor.transform(row);
+            assert_eq!(loaded_preprocessor.transform(row), transformed);
+            assert_eq!(loaded_network.calculate(&transformed), network.calculate(&transformed));
+        }
+    }
+}
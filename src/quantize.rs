@@ -0,0 +1,452 @@
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "std")]
+use crate::dataset::{self, Dataset};
+use crate::float;
+use crate::modules::Softmax;
+use crate::nodes_layers::{Activation, Module, Network};
+
+//Post-training quantization of a Network to 8 bit integers, which makes the weights four times smaller
+//and lets devices without floating point hardware run it with integer arithmetic only.
+//
+//A real value x is stored as the integer q = round(x / scale) + zero_point, clamped to [-128; 127].
+//The scales and zero points of the values are found by calibration: the float network is run on sample inputs,
+//and the smallest and largest inputs, weighted sums and values of each layer are recorded.
+//Weights are quantized symmetrically (zero point 0), with one scale for the whole layer or one for each node (channel).
+//
+//In the integer forward pass each node adds (input - input zero point) * weight to its bias, which is quantized to an i32,
+//and the sum is scaled to the 8 bit weighted sum with a fixed point multiplier. The activation function is a table
+//with one entry for each of the 256 possible weighted sums, made when quantizing, so no float is needed while calculating.
+//A Softmax at the end is left as floats: the quantized values before it are dequantized and put through a float softmax.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Granularity {
+//How many scales the weights of a dense layer get.
+    PerLayer,
+    PerChannel, //One for each node, which keeps more precision when the nodes' weights are of different sizes.
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct QuantizationParameters {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+impl QuantizationParameters {
+    pub fn from_range(min: f32, max: f32) -> QuantizationParameters {
+    //Spreads the 256 integers over [min; max], widened to include 0 so that 0 is stored exactly.
+        let min = min.min(0.0);
+        let max = max.max(0.0);
+        let scale = if max > min {(max - min) / 255.0} else {1.0};
+        let zero_point = float::round(-128.0 - min / scale) as i32;
+        QuantizationParameters {
+            scale,
+            zero_point: zero_point.clamp(-128, 127),
+        }
+    }
+
+    pub fn quantize(&self, value: f32) -> i8 {
+        let quantized = float::round(value / self.scale) as i32 + self.zero_point;
+        quantized.clamp(-128, 127) as i8
+    }
+
+    pub fn dequantize(&self, value: i8) -> f32 {
+        self.scale * (value as i32 - self.zero_point) as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct QuantizedLayer {
+//A dense layer with 8 bit weights.
+    pub input_count: usize,
+    pub node_count: usize,
+    pub weights: Vec<i8>, //The weights of node n are weights[n * input_count..(n + 1) * input_count].
+    pub weight_scales: Vec<f32>, //One for each node. With Granularity::PerLayer they are all the same.
+    pub biases: Vec<i32>, //Quantized with the scale input.scale * weight_scales[node] and zero point 0.
+    pub multipliers: Vec<i32>, //The fixed point multipliers from the sums to the weighted sums, see requantize.
+    pub shifts: Vec<u32>,
+    pub input: QuantizationParameters,
+    pub sum: QuantizationParameters, //Of the weighted sums, before the activation function.
+    pub output: QuantizationParameters,
+    pub lookup: Vec<i8>, //The quantized value for each quantized weighted sum, at position weighted sum + 128.
+}
+
+#[derive(Clone, Debug)]
+pub enum QuantizedStep {
+    Dense(QuantizedLayer),
+    Lookup {table: Vec<i8>, output: QuantizationParameters}, //An activation layer, as a table from the previous values to its own.
+}
+
+#[derive(Clone, Debug)]
+pub struct QuantizedNetwork {
+    pub steps: Vec<QuantizedStep>,
+    pub input: QuantizationParameters,
+    pub output: QuantizationParameters, //Of the values forward gives, which are the values before the softmax if there is one.
+    pub granularity: Granularity,
+    pub softmax: bool, //The network ends with a Softmax, which calculate applies to the dequantized outputs.
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum QuantizationError {
+    NoCalibrationInputs,
+    UnsupportedModule(String), //The description of a module that can't be quantized, e.g. a convolution.
+    SoftmaxNotLast, //A softmax is only supported as the last module, apart from modules that pass the values on.
+    MultiplierTooLarge(f64), //The weighted sums of a layer need a larger scale than its sums, so the calibration ranges are probably wrong.
+}
+
+impl fmt::Display for QuantizationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuantizationError::NoCalibrationInputs => write!(f, "At least one calibration input is needed to find the ranges of the values"),
+            QuantizationError::UnsupportedModule(description) => write!(f, "Only dense layers, activation layers, dropouts and a softmax at the end can be quantized, not {}", description),
+            QuantizationError::SoftmaxNotLast => write!(f, "A softmax can only be quantized as the last module"),
+            QuantizationError::MultiplierTooLarge(multiplier) => write!(f, "The multiplier {} is too large, the calibration ranges are probably wrong", multiplier),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for QuantizationError {}
+
+#[derive(Clone, Copy, Debug)]
+struct Range {
+    min: f32,
+    max: f32,
+}
+
+impl Range {
+    fn new() -> Range {
+        Range {
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+        }
+    }
+
+    fn add(&mut self, values: &[f32]) {
+        for value in values.iter() {
+            self.min = self.min.min(*value);
+            self.max = self.max.max(*value);
+        }
+    }
+
+    fn parameters(&self) -> QuantizationParameters {
+        if self.min > self.max { // Nothing was added
+            return QuantizationParameters::from_range(0.0, 0.0)
+        }
+        QuantizationParameters::from_range(self.min, self.max)
+    }
+}
+
+pub fn quantize(network: &Network, calibration_inputs: &[Vec<f32>], granularity: Granularity) -> Result<QuantizedNetwork, QuantizationError> {
+//Quantizes a network of dense layers, activation layers, dropouts and a softmax at the end, calibrated on the given inputs, which should look like the real ones.
+    if calibration_inputs.is_empty() {
+        return Err(QuantizationError::NoCalibrationInputs)
+    }
+    let module_count = network.layers.len();
+    let mut input_range = Range::new();
+    let mut sum_ranges = vec![Range::new(); module_count];
+    let mut value_ranges = vec![Range::new(); module_count];
+    for inputs in calibration_inputs.iter() {
+        input_range.add(inputs);
+        let values = network.calculate(inputs);
+        for module_num in 0..module_count {
+            let previous_values = if module_num == 0 {inputs} else {&values[module_num - 1]};
+            if let Some(layer) = network.layers[module_num].as_layer() {
+                let sums: Vec<f32> = layer.nodes.iter().map(|node| node.weighted_sum(previous_values)).collect();
+                sum_ranges[module_num].add(&sums);
+            }
+            value_ranges[module_num].add(&values[module_num]);
+        }
+    }
+
+    let input = input_range.parameters();
+    let mut current = input;
+    let mut steps = Vec::new();
+    let mut softmax = false;
+    for (module_num, module) in network.layers.iter().enumerate() {
+        if softmax && !module.passes_values_on() {
+            return Err(QuantizationError::SoftmaxNotLast)
+        }
+        if let Some(layer) = module.as_layer() {
+            let input_count = if layer.node_count > 0 {layer.nodes[0].weights.len()} else {0};
+            let mut weight_scales = Vec::new();
+            for node in layer.nodes.iter() {
+                weight_scales.push(node.weights.iter().fold(0.0f32, |largest, weight| largest.max(float::abs(*weight))) / 127.0);
+            }
+            if granularity == Granularity::PerLayer {
+                let largest = weight_scales.iter().fold(0.0f32, |largest, scale| largest.max(*scale));
+                weight_scales = vec![largest; layer.node_count];
+            }
+            for scale in weight_scales.iter_mut() {
+                if *scale == 0.0 {
+                    *scale = 1.0;
+                }
+            }
+            let sum = sum_ranges[module_num].parameters();
+            let output = value_ranges[module_num].parameters();
+            let mut weights = Vec::new();
+            let mut biases = Vec::new();
+            let mut multipliers = Vec::new();
+            let mut shifts = Vec::new();
+            for (node, weight_scale) in layer.nodes.iter().zip(weight_scales.iter()) {
+                for weight in node.weights.iter() {
+                    weights.push(float::round(weight / weight_scale).clamp(-127.0, 127.0) as i8);
+                }
+                let bias_scale = current.scale * weight_scale;
+                biases.push(float::round(node.bias / bias_scale).clamp(-2147483648.0, 2147483520.0) as i32);
+                let (multiplier, shift) = fixed_point(bias_scale as f64 / sum.scale as f64)?;
+                multipliers.push(multiplier);
+                shifts.push(shift);
+            }
+            steps.push(QuantizedStep::Dense(QuantizedLayer {
+                input_count,
+                node_count: layer.node_count,
+                weights,
+                weight_scales,
+                biases,
+                multipliers,
+                shifts,
+                input: current,
+                sum,
+                output,
+                lookup: lookup_table(&layer.activation, &sum, &output),
+            }));
+            current = output;
+        } else if let Some(activation_layer) = module.as_activation_layer() {
+            let output = value_ranges[module_num].parameters();
+            let table = lookup_table(&activation_layer.activation, &current, &output);
+            steps.push(QuantizedStep::Lookup {table, output});
+            current = output;
+        } else if module.as_softmax().is_some() {
+            softmax = true;
+        } else if !module.passes_values_on() {
+            return Err(QuantizationError::UnsupportedModule(module.describe()))
+        }
+    }
+    Ok(QuantizedNetwork {
+        steps,
+        input,
+        output: current,
+        granularity,
+        softmax,
+    })
+}
+
+fn lookup_table(activation: &Activation, input: &QuantizationParameters, output: &QuantizationParameters) -> Vec<i8> {
+//The quantized value of the activation function for each of the 256 quantized inputs.
+    let mut table = Vec::new();
+    for quantized in -128..=127 {
+        table.push(output.quantize(activation.apply(input.dequantize(quantized as i8))));
+    }
+    table
+}
+
+fn fixed_point(multiplier: f64) -> Result<(i32, u32), QuantizationError> {
+//Writes a positive real multiplier as an integer in [2^30; 2^31[ and a shift, so that multiplier = integer / 2^shift.
+    if !multiplier.is_finite() {
+        return Err(QuantizationError::MultiplierTooLarge(multiplier))
+    }
+    if multiplier <= 0.0 {
+        return Ok((0, 1))
+    }
+    let mut mantissa = multiplier;
+    let mut exponent: i32 = 0;
+    while mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+    while mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+    let mut integer = (mantissa * 2147483648.0 + 0.5) as i64;
+    if integer == 2147483648 {
+        integer /= 2;
+        exponent += 1;
+    }
+    let shift = 31 - exponent;
+    if shift > 62 { // So small that every sum becomes 0
+        return Ok((0, 1))
+    }
+    if shift < 1 {
+        return Err(QuantizationError::MultiplierTooLarge(multiplier))
+    }
+    Ok((integer as i32, shift as u32))
+}
+
+fn requantize(sum: i32, multiplier: i32, shift: u32) -> i32 {
+//sum * multiplier / 2^shift, rounded to the nearest integer.
+    let product = sum as i64 * multiplier as i64;
+    let rounded = (product + (1i64 << (shift - 1))) >> shift;
+    rounded.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+}
+
+impl QuantizedLayer {
+    pub fn forward(&self, inputs: &[i8]) -> Vec<i8> {
+    //The integer forward pass of the layer.
+        if inputs.len() != self.input_count {
+            panic!("The number of inputs ({}) doesn't match the number of weights ({})", inputs.len(), self.input_count);
+        }
+        let mut values = Vec::new();
+        for node_num in 0..self.node_count {
+            let weights = &self.weights[node_num * self.input_count..(node_num + 1) * self.input_count];
+            let mut sum = self.biases[node_num];
+            for pos_num in 0..self.input_count {
+                sum = sum.wrapping_add((inputs[pos_num] as i32 - self.input.zero_point) * weights[pos_num] as i32);
+            }
+            let weighted_sum = requantize(sum, self.multipliers[node_num], self.shifts[node_num]) + self.sum.zero_point;
+            values.push(self.lookup[(weighted_sum.clamp(-128, 127) + 128) as usize]);
+        }
+        values
+    }
+}
+
+impl QuantizedNetwork {
+    pub fn quantize_inputs(&self, inputs: &[f32]) -> Vec<i8> {
+        inputs.iter().map(|value| self.input.quantize(*value)).collect()
+    }
+
+    pub fn dequantize_outputs(&self, outputs: &[i8]) -> Vec<f32> {
+        outputs.iter().map(|value| self.output.dequantize(*value)).collect()
+    }
+
+    pub fn forward(&self, inputs: &[i8]) -> Vec<i8> {
+    //The integer-only forward pass, from quantized inputs to quantized outputs. With a softmax, the outputs are the values before it.
+        let mut values = inputs.to_vec();
+        for step in self.steps.iter() {
+            values = match step {
+                QuantizedStep::Dense(layer) => layer.forward(&values),
+                QuantizedStep::Lookup {table, ..} => values.iter().map(|value| table[(*value as i32 + 128) as usize]).collect(),
+            };
+        }
+        values
+    }
+
+    pub fn calculate(&self, inputs: &[f32]) -> Vec<f32> {
+    //Quantizes the inputs, runs the integer forward pass, and turns the outputs back into floats, to compare with Network::calculate.
+        let outputs = self.dequantize_outputs(&self.forward(&self.quantize_inputs(inputs)));
+        if self.softmax {
+            return Softmax::new().forward(&outputs)
+        }
+        outputs
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+    //The size of the weights, biases, scales and tables, which is what would be stored on a device.
+        let mut size = 0;
+        for step in self.steps.iter() {
+            size += match step {
+                QuantizedStep::Dense(layer) => layer.weights.len() + 4 * (layer.biases.len() + layer.multipliers.len() + layer.shifts.len()) + layer.lookup.len(),
+                QuantizedStep::Lookup {table, ..} => table.len(),
+            };
+        }
+        size
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct QuantizationReport {
+    pub float_accuracy: f32, //The share of the samples the float network gets right.
+    pub quantized_accuracy: f32,
+    pub accuracy_drop: f32, //float_accuracy - quantized_accuracy
+    pub largest_difference: f32, //The largest difference between an output of the two networks.
+    pub mean_difference: f32,
+    pub float_size: usize, //Bytes of parameters of the float network.
+    pub quantized_size: usize,
+}
+
+#[cfg(feature = "std")]
+pub fn compare(network: &Network, quantized: &QuantizedNetwork, data: &Dataset) -> QuantizationReport {
+//Runs both networks on the data and reports how much the quantization changes the outputs and the accuracy.
+//A sample is right when the largest output is the right class for one-hot outputs, and else when every output is on the same side of 0.5 as the desired one.
+    let mut float_correct = 0;
+    let mut quantized_correct = 0;
+    let mut largest_difference = 0.0f32;
+    let mut difference_sum = 0.0;
+    let mut output_count = 0;
+    for (inputs, desired_outputs) in data.inputs.iter().zip(data.outputs.iter()) {
+        let values = network.calculate(inputs);
+        let float_outputs = &values[values.len() - 1];
+        let quantized_outputs = quantized.calculate(inputs);
+        if is_correct(float_outputs, desired_outputs) {
+            float_correct += 1;
+        }
+        if is_correct(&quantized_outputs, desired_outputs) {
+            quantized_correct += 1;
+        }
+        for num in 0..float_outputs.len() {
+            let difference = (float_outputs[num] - quantized_outputs[num]).abs();
+            largest_difference = largest_difference.max(difference);
+            difference_sum += difference;
+            output_count += 1;
+        }
+    }
+    let float_accuracy = float_correct as f32 / data.len().max(1) as f32;
+    let quantized_accuracy = quantized_correct as f32 / data.len().max(1) as f32;
+    QuantizationReport {
+        float_accuracy,
+        quantized_accuracy,
+        accuracy_drop: float_accuracy - quantized_accuracy,
+        largest_difference,
+        mean_difference: difference_sum / output_count.max(1) as f32,
+        float_size: 4 * network.layers.iter().map(|module| module.parameters().len()).sum::<usize>(),
+        quantized_size: quantized.size_in_bytes(),
+    }
+}
+
+#[cfg(feature = "std")]
+fn is_correct(outputs: &[f32], desired_outputs: &[f32]) -> bool {
+    let one_hot = desired_outputs.len() > 1 && desired_outputs.iter().filter(|value| **value == 1.0).count() == 1
+        && desired_outputs.iter().all(|value| *value == 0.0 || *value == 1.0);
+    if one_hot {
+        return dataset::decode_one_hot(outputs) == dataset::decode_one_hot(desired_outputs)
+    }
+    outputs.iter().zip(desired_outputs.iter()).all(|(output, desired)| (*output > 0.5) == (*desired > 0.5))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::conv::Conv1d;
+    use crate::nodes_layers::Layer;
+    use crate::rng::SimpleRng;
+
+    #[test]
+    fn softmax_at_the_end_is_calculated_in_floats() {
+        let mut rng = SimpleRng::new(4);
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer::from_rng(3, 8, Activation::Relu, &mut rng)));
+        network.push(Box::new(Layer::from_rng(8, 4, Activation::Identity, &mut rng)));
+        network.push(Box::new(Softmax::new()));
+        let inputs: Vec<Vec<f32>> = (0..50).map(|_| (0..3).map(|_| rng.gen_range(-1.0, 1.0)).collect()).collect();
+        let quantized = quantize(&network, &inputs, Granularity::PerChannel).unwrap();
+        assert!(quantized.softmax);
+        for sample in inputs.iter() {
+            let values = network.calculate(sample);
+            let expected = &values[values.len() - 1];
+            let outputs = quantized.calculate(sample);
+            assert!((outputs.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            for num in 0..outputs.len() {
+                assert!((outputs[num] - expected[num]).abs() < 0.05, "{:?} != {:?}", outputs, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_modules_are_errors() {
+        let inputs = vec![vec![0.5; 4]];
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Conv1d::new(1, 1, 4, 2, 1, 0)));
+        assert!(matches!(quantize(&network, &inputs, Granularity::PerLayer), Err(QuantizationError::UnsupportedModule(_))));
+
+        let mut rng = SimpleRng::new(5);
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Layer::from_rng(4, 3, Activation::Identity, &mut rng)));
+        network.push(Box::new(Softmax::new()));
+        network.push(Box::new(Layer::from_rng(3, 2, Activation::Sigmoid, &mut rng)));
+        assert_eq!(quantize(&network, &inputs, Granularity::PerLayer).unwrap_err(), QuantizationError::SoftmaxNotLast);
+        assert_eq!(quantize(&network, &Vec::new(), Granularity::PerLayer).unwrap_err(), QuantizationError::NoCalibrationInputs);
+    }
+}
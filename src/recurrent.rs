@@ -0,0 +1,467 @@
+use crate::nodes_layers::{Activation, Layer, Module, Network};
+
+//Recurrent networks read a sequence of inputs one step at a time and carry a state from each step to the next.
+//A cell's state starts out as zeros, and its first hidden_size values are the hidden state that is passed on to the rest of the network.
+
+fn join(first: &[f32], second: &[f32]) -> Vec<f32> {
+//Puts two lists after each other, e.g. the input of a step and the hidden state of the step before.
+    let mut joined = first.to_vec();
+    joined.extend_from_slice(second);
+    joined
+}
+
+pub trait RecurrentCell {
+//The part of a recurrent network that is used at every step of a sequence.
+//The cells are built from dense Layers (called gates), which store their own adjusts like in any other Network.
+    fn input_size(&self) -> usize;
+
+    fn hidden_size(&self) -> usize;
+
+    fn state_size(&self) -> usize {
+    //The number of values carried between steps. LSTM cells carry a cell state along with the hidden state.
+        self.hidden_size()
+    }
+
+    fn step(&self, input: &[f32], state: &[f32]) -> Vec<f32>;
+    //Calculates the state after one step from the input of the step and the state before it.
+
+    fn backward_step(&mut self, input: &[f32], previous_state: &[f32], state: &[f32], state_deltas: &[f32]) -> (Vec<f32>, Vec<f32>);
+    //Takes how the cost changes with the state after a step, and adds to the adjusts of the gates.
+    //Returns how the cost changes with the input of the step and with the state before it.
+
+    fn gates(&self) -> Vec<&Layer>;
+
+    fn gates_mut(&mut self) -> Vec<&mut Layer>;
+
+    fn parameters(&self) -> Vec<f32> {
+    //The parameters of every gate after each other.
+        let mut parameters = Vec::new();
+        for gate in self.gates() {
+            parameters.extend_from_slice(&gate.parameters());
+        }
+        parameters
+    }
+
+    fn set_parameters(&mut self, parameters: &[f32]) {
+        let mut pos = 0;
+        for gate in self.gates_mut() {
+            let count = gate.parameters().len();
+            if pos + count > parameters.len() {
+                panic!("Received too few parameters ({}) for the cell", parameters.len());
+            }
+            gate.set_parameters(&parameters[pos..pos + count]);
+            pos += count;
+        }
+        if pos != parameters.len() {
+            panic!("The number of parameters ({}) doesn't match the cell ({})", parameters.len(), pos);
+        }
+    }
+
+    fn gradients(&self) -> Vec<f32> {
+        let mut gradients = Vec::new();
+        for gate in self.gates() {
+            gradients.extend_from_slice(&gate.gradients());
+        }
+        gradients
+    }
+
+    fn adjust(&mut self, learning_rate: f32) {
+        for gate in self.gates_mut() {
+            gate.adjust(learning_rate);
+        }
+    }
+
+    fn box_clone(&self) -> Box<dyn RecurrentCell>;
+}
+
+impl Clone for Box<dyn RecurrentCell> {
+    fn clone(&self) -> Box<dyn RecurrentCell> {
+        self.box_clone()
+    }
+}
+
+#[derive(Clone)]
+pub struct ElmanCell {
+//The simplest recurrent cell: new hidden state = tanh(weights * [input, hidden state] + bias).
+    pub hidden: Layer,
+    input_size: usize,
+}
+
+impl ElmanCell {
+    pub fn new(input_size: usize, hidden_size: usize) -> ElmanCell {
+        ElmanCell {
+            hidden: Layer::with_activation(input_size + hidden_size, hidden_size, Activation::Tanh),
+            input_size,
+        }
+    }
+}
+
+impl RecurrentCell for ElmanCell {
+    fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.hidden.node_count
+    }
+
+    fn step(&self, input: &[f32], state: &[f32]) -> Vec<f32> {
+        self.hidden.calculate(&join(input, state))
+    }
+
+    fn backward_step(&mut self, input: &[f32], previous_state: &[f32], state: &[f32], state_deltas: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let joined_deltas = self.hidden.backward(&join(input, previous_state), state, state_deltas);
+        (joined_deltas[..self.input_size].to_vec(), joined_deltas[self.input_size..].to_vec())
+    }
+
+    fn gates(&self) -> Vec<&Layer> {
+        vec![&self.hidden]
+    }
+
+    fn gates_mut(&mut self) -> Vec<&mut Layer> {
+        vec![&mut self.hidden]
+    }
+
+    fn box_clone(&self) -> Box<dyn RecurrentCell> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct GruCell {
+//A gated recurrent unit. The update gate decides how much of the old hidden state to keep,
+//and the reset gate decides how much of it to use when finding the candidate for the new hidden state.
+    pub update: Layer,
+    pub reset: Layer,
+    pub candidate: Layer,
+    input_size: usize,
+}
+
+impl GruCell {
+    pub fn new(input_size: usize, hidden_size: usize) -> GruCell {
+        GruCell {
+            update: Layer::new(input_size + hidden_size, hidden_size),
+            reset: Layer::new(input_size + hidden_size, hidden_size),
+            candidate: Layer::with_activation(input_size + hidden_size, hidden_size, Activation::Tanh),
+            input_size,
+        }
+    }
+
+    fn gate_values(&self, input: &[f32], state: &[f32]) -> (Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>) {
+    //Returns the update gate, the reset gate, the reset hidden state and the candidate.
+        let joined = join(input, state);
+        let update = self.update.calculate(&joined);
+        let reset = self.reset.calculate(&joined);
+        let mut reset_state = Vec::new();
+        for num in 0..state.len() {
+            reset_state.push(reset[num] * state[num]);
+        }
+        let candidate = self.candidate.calculate(&join(input, &reset_state));
+        (update, reset, reset_state, candidate)
+    }
+}
+
+impl RecurrentCell for GruCell {
+    fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.update.node_count
+    }
+
+    fn step(&self, input: &[f32], state: &[f32]) -> Vec<f32> {
+        let (update, _, _, candidate) = self.gate_values(input, state);
+        let mut new_state = Vec::new();
+        for num in 0..state.len() {
+            new_state.push((1.0 - update[num]) * candidate[num] + update[num] * state[num]);
+        }
+        new_state
+    }
+
+    fn backward_step(&mut self, input: &[f32], previous_state: &[f32], _state: &[f32], state_deltas: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let (update, reset, reset_state, candidate) = self.gate_values(input, previous_state);
+        let hidden_size = previous_state.len();
+        let mut update_deltas = Vec::new();
+        let mut candidate_deltas = Vec::new();
+        let mut previous_deltas = Vec::new();
+        for num in 0..hidden_size {
+            update_deltas.push(state_deltas[num] * (previous_state[num] - candidate[num]));
+            candidate_deltas.push(state_deltas[num] * (1.0 - update[num]));
+            previous_deltas.push(state_deltas[num] * update[num]);
+        }
+
+        let candidate_joined_deltas = self.candidate.backward(&join(input, &reset_state), &candidate, &candidate_deltas);
+        let mut input_deltas = candidate_joined_deltas[..self.input_size].to_vec();
+        let mut reset_deltas = Vec::new();
+        for num in 0..hidden_size {
+            let reset_state_delta = candidate_joined_deltas[self.input_size + num];
+            reset_deltas.push(reset_state_delta * previous_state[num]);
+            previous_deltas[num] += reset_state_delta * reset[num];
+        }
+
+        let joined = join(input, previous_state);
+        let update_joined_deltas = self.update.backward(&joined, &update, &update_deltas);
+        let reset_joined_deltas = self.reset.backward(&joined, &reset, &reset_deltas);
+        for num in 0..self.input_size {
+            input_deltas[num] += update_joined_deltas[num] + reset_joined_deltas[num];
+        }
+        for num in 0..hidden_size {
+            previous_deltas[num] += update_joined_deltas[self.input_size + num] + reset_joined_deltas[self.input_size + num];
+        }
+        (input_deltas, previous_deltas)
+    }
+
+    fn gates(&self) -> Vec<&Layer> {
+        vec![&self.update, &self.reset, &self.candidate]
+    }
+
+    fn gates_mut(&mut self) -> Vec<&mut Layer> {
+        vec![&mut self.update, &mut self.reset, &mut self.candidate]
+    }
+
+    fn box_clone(&self) -> Box<dyn RecurrentCell> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct LstmCell {
+//A long short-term memory cell. Besides the hidden state it carries a cell state, which the forget, input and output gates
+//decide how to keep, add to and read from. The state is the hidden state followed by the cell state.
+    pub input_gate: Layer,
+    pub forget_gate: Layer,
+    pub output_gate: Layer,
+    pub candidate: Layer,
+    input_size: usize,
+}
+
+impl LstmCell {
+    pub fn new(input_size: usize, hidden_size: usize) -> LstmCell {
+        LstmCell {
+            input_gate: Layer::new(input_size + hidden_size, hidden_size),
+            forget_gate: Layer::new(input_size + hidden_size, hidden_size),
+            output_gate: Layer::new(input_size + hidden_size, hidden_size),
+            candidate: Layer::with_activation(input_size + hidden_size, hidden_size, Activation::Tanh),
+            input_size,
+        }
+    }
+
+    fn gate_values(&self, input: &[f32], state: &[f32]) -> (Vec<f32>, Vec<f32>, Vec<f32>, Vec<f32>) {
+    //Returns the input gate, the forget gate, the output gate and the candidate.
+        let joined = join(input, &state[..self.hidden_size()]);
+        (self.input_gate.calculate(&joined),
+         self.forget_gate.calculate(&joined),
+         self.output_gate.calculate(&joined),
+         self.candidate.calculate(&joined))
+    }
+}
+
+impl RecurrentCell for LstmCell {
+    fn input_size(&self) -> usize {
+        self.input_size
+    }
+
+    fn hidden_size(&self) -> usize {
+        self.input_gate.node_count
+    }
+
+    fn state_size(&self) -> usize {
+        2 * self.hidden_size()
+    }
+
+    fn step(&self, input: &[f32], state: &[f32]) -> Vec<f32> {
+        let hidden_size = self.hidden_size();
+        let (input_gate, forget_gate, output_gate, candidate) = self.gate_values(input, state);
+        let mut hidden = Vec::new();
+        let mut cell = Vec::new();
+        for num in 0..hidden_size {
+            let cell_value = forget_gate[num] * state[hidden_size + num] + input_gate[num] * candidate[num];
+            cell.push(cell_value);
+            hidden.push(output_gate[num] * cell_value.tanh());
+        }
+        join(&hidden, &cell)
+    }
+
+    fn backward_step(&mut self, input: &[f32], previous_state: &[f32], state: &[f32], state_deltas: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let hidden_size = self.hidden_size();
+        let (input_gate, forget_gate, output_gate, candidate) = self.gate_values(input, previous_state);
+        let mut input_gate_deltas = Vec::new();
+        let mut forget_gate_deltas = Vec::new();
+        let mut output_gate_deltas = Vec::new();
+        let mut candidate_deltas = Vec::new();
+        let mut previous_cell_deltas = Vec::new();
+        for num in 0..hidden_size {
+            let cell_tanh = state[hidden_size + num].tanh();
+            let cell_delta = state_deltas[hidden_size + num] + state_deltas[num] * output_gate[num] * (1.0 - cell_tanh * cell_tanh);
+            output_gate_deltas.push(state_deltas[num] * cell_tanh);
+            forget_gate_deltas.push(cell_delta * previous_state[hidden_size + num]);
+            input_gate_deltas.push(cell_delta * candidate[num]);
+            candidate_deltas.push(cell_delta * input_gate[num]);
+            previous_cell_deltas.push(cell_delta * forget_gate[num]);
+        }
+
+        let joined = join(input, &previous_state[..hidden_size]);
+        let mut joined_deltas = vec![0.0; joined.len()];
+        let gate_deltas = [self.input_gate.backward(&joined, &input_gate, &input_gate_deltas),
+            self.forget_gate.backward(&joined, &forget_gate, &forget_gate_deltas),
+            self.output_gate.backward(&joined, &output_gate, &output_gate_deltas),
+            self.candidate.backward(&joined, &candidate, &candidate_deltas)];
+        for deltas in gate_deltas.iter() {
+            for num in 0..joined.len() {
+                joined_deltas[num] += deltas[num];
+            }
+        }
+        let input_deltas = joined_deltas[..self.input_size].to_vec();
+        let previous_deltas = join(&joined_deltas[self.input_size..], &previous_cell_deltas);
+        (input_deltas, previous_deltas)
+    }
+
+    fn gates(&self) -> Vec<&Layer> {
+        vec![&self.input_gate, &self.forget_gate, &self.output_gate, &self.candidate]
+    }
+
+    fn gates_mut(&mut self) -> Vec<&mut Layer> {
+        vec![&mut self.input_gate, &mut self.forget_gate, &mut self.output_gate, &mut self.candidate]
+    }
+
+    fn box_clone(&self) -> Box<dyn RecurrentCell> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct RecurrentNetwork {
+//A recurrent cell that reads a sequence, followed by a Network that turns the last hidden state into the outputs.
+    pub cell: Box<dyn RecurrentCell>,
+    pub output: Network,
+    pub truncation: usize, //The number of steps, counted from the end of the sequence, that the adjusts are found through.
+}
+
+impl RecurrentNetwork {
+    pub fn new(cell: Box<dyn RecurrentCell>, output: Network, truncation: usize) -> RecurrentNetwork {
+    //The cell is adjusted with the learning rate of the output network.
+        RecurrentNetwork {
+            cell,
+            output,
+            truncation,
+        }
+    }
+
+    pub fn run(&self, sequence: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    //Calculates the state at every step. states[0] is the starting state of zeros, and states[num + 1] is the state after sequence[num].
+        let mut states = vec![vec![0.0; self.cell.state_size()]];
+        for num in 0..sequence.len() {
+            if sequence[num].len() != self.cell.input_size() {
+                panic!("Step {} of the sequence has {} values, but the cell expects {}", num, sequence[num].len(), self.cell.input_size());
+            }
+            let next_state = self.cell.step(&sequence[num], &states[num]);
+            states.push(next_state);
+        }
+        states
+    }
+
+    pub fn calculate(&self, sequence: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    //Reads the whole sequence and calculates the values of the output network from the last hidden state, like Network::calculate.
+        let states = self.run(sequence);
+        let hidden = states[sequence.len()][..self.cell.hidden_size()].to_vec();
+        self.output.calculate(&hidden)
+    }
+
+    pub fn find_cost(&self, sequence: &[Vec<f32>], desired_outputs: &[f32]) -> f32 {
+        let hidden = self.run(sequence)[sequence.len()][..self.cell.hidden_size()].to_vec();
+        self.output.find_cost(&hidden, desired_outputs)
+    }
+
+    pub fn find_make_adjust(&mut self, sequence: &[Vec<f32>], desired_outputs: &[f32]) {
+    //Truncated backpropagation through time: the output network is adjusted as usual, and its input deltas are sent back
+    //through the last truncation steps of the sequence. The adjusts of every step are added up before the cell is adjusted.
+        let states = self.run(sequence);
+        let step_count = sequence.len();
+        let hidden_size = self.cell.hidden_size();
+        let hidden = states[step_count][..hidden_size].to_vec();
+        let mut state_deltas = self.output.find_adjusts(&hidden, desired_outputs);
+        state_deltas.extend_from_slice(&vec![0.0; self.cell.state_size() - hidden_size]);
+
+        let first_step = step_count - self.truncation.min(step_count);
+        for num in (first_step..step_count).rev() {
+            let (_, previous_deltas) = self.cell.backward_step(&sequence[num], &states[num], &states[num + 1], &state_deltas);
+            state_deltas = previous_deltas;
+        }
+        self.cell.adjust(self.output.learning_rate);
+        self.output.adjust();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost(cell: &dyn RecurrentCell, input: &[f32], state: &[f32]) -> f32 {
+    //A cost that weights every value of the new state differently.
+        let new_state = cell.step(input, state);
+        let mut cost = 0.0;
+        for num in 0..new_state.len() {
+            cost += new_state[num] * cost_weight(num);
+        }
+        cost
+    }
+
+    fn cost_weight(num: usize) -> f32 {
+        (num % 5) as f32 * 0.4 - 0.7
+    }
+
+    fn finite_difference(values: &[f32], num: usize, cost_of: &dyn Fn(&[f32]) -> f32) -> f32 {
+    //How the cost changes when one value is moved a little up and down.
+        let step = 0.005;
+        let mut moved = values.to_vec();
+        moved[num] = values[num] + step;
+        let higher = cost_of(&moved);
+        moved[num] = values[num] - step;
+        let lower = cost_of(&moved);
+        (higher - lower) / (2.0 * step)
+    }
+
+    fn check_gradients(cell: &mut dyn RecurrentCell) {
+    //Compares the results of backward_step with finite differences for the parameters, the input and the state before the step.
+        let input: Vec<f32> = (0..cell.input_size()).map(|num| num as f32 * 0.3 - 0.4).collect();
+        let previous_state: Vec<f32> = (0..cell.state_size()).map(|num| 0.5 - num as f32 * 0.2).collect();
+        let state = cell.step(&input, &previous_state);
+        let state_deltas: Vec<f32> = (0..state.len()).map(cost_weight).collect();
+        let (input_deltas, previous_deltas) = cell.backward_step(&input, &previous_state, &state, &state_deltas);
+        let gradients = cell.gradients();
+        let parameters = cell.parameters();
+        let tolerance = 2e-3;
+        for num in 0..parameters.len() {
+            let expected = finite_difference(&parameters, num, &|moved| {
+                let mut moved_cell = cell.box_clone();
+                moved_cell.set_parameters(moved);
+                cost(&*moved_cell, &input, &previous_state)
+            });
+            assert!((gradients[num] - expected).abs() < tolerance, "parameter {}: {} vs {}", num, gradients[num], expected);
+        }
+        for num in 0..input.len() {
+            let expected = finite_difference(&input, num, &|moved| cost(cell, moved, &previous_state));
+            assert!((input_deltas[num] - expected).abs() < tolerance, "input {}: {} vs {}", num, input_deltas[num], expected);
+        }
+        for num in 0..previous_state.len() {
+            let expected = finite_difference(&previous_state, num, &|moved| cost(cell, &input, moved));
+            assert!((previous_deltas[num] - expected).abs() < tolerance, "state {}: {} vs {}", num, previous_deltas[num], expected);
+        }
+    }
+
+    #[test]
+    fn elman_gradients_match_finite_differences() {
+        check_gradients(&mut ElmanCell::new(3, 4));
+    }
+
+    #[test]
+    fn gru_gradients_match_finite_differences() {
+        check_gradients(&mut GruCell::new(3, 4));
+    }
+
+    #[test]
+    fn lstm_gradients_match_finite_differences() {
+        check_gradients(&mut LstmCell::new(3, 4));
+    }
+}
@@ -0,0 +1,63 @@
+use crate::float;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SimpleRng {
+//A small xorshift random number generator. Unlike rand::thread_rng, its whole state is one number,
+//so it can be seeded for repeatable runs and saved in a checkpoint.
+    pub state: u64,
+}
+
+impl SimpleRng {
+    pub fn new(seed: u64) -> SimpleRng {
+    //The state of a xorshift generator must never be 0, so the seed is mixed first.
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+        if state == 0 {
+            state = 1;
+        }
+        SimpleRng {
+            state,
+        }
+    }
+
+    pub fn from_state(state: u64) -> SimpleRng {
+    //Continues from a state saved earlier.
+        if state == 0 {
+            panic!("The state of the random number generator can't be 0");
+        }
+        SimpleRng {
+            state,
+        }
+    }
+
+    pub fn gen_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn gen_f32(&mut self) -> f32 {
+    //Random number in the interval [0; 1[
+        (self.gen_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+    //Random number in the interval [low; high[
+        low + (high - low) * self.gen_f32()
+    }
+
+    pub fn gen_normal(&mut self) -> f32 {
+    //Random number from the normal distribution with mean 0 and standard deviation 1, using the Box-Muller transform.
+        let first = 1.0 - self.gen_f32(); // In ]0; 1], so the logarithm is finite
+        let second = self.gen_f32();
+        float::sqrt(-2.0 * float::ln(first)) * float::cos(2.0 * core::f32::consts::PI * second)
+    }
+
+    pub fn gen_index(&mut self, count: usize) -> usize {
+    //Random number in 0..count
+        (self.gen_u64() % count as u64) as usize
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::nodes_layers::Network;
+
+//Calculates the outputs of a Network again and again without allocating, e.g. in a real-time loop.
+//Network::calculate makes new lists for the values of every module on every call, while a session makes them once
+//and writes into them with Module::forward_into. Modules that don't override forward_into still allocate.
+
+pub struct InferenceSession<'a> {
+    network: &'a Network,
+    input_count: usize,
+    values: Vec<Vec<f32>>, //values[num] are the values of module num from the last call, like Network::calculate gives them.
+}
+
+impl<'a> InferenceSession<'a> {
+    pub fn new(network: &'a Network, input_count: usize) -> InferenceSession<'a> {
+    //Makes room for the values of every module of the network, for input_count inputs.
+        if network.layers.is_empty() {
+            panic!("The network has no modules to calculate with");
+        }
+        let mut values = Vec::new();
+        let mut count = input_count;
+        for module in network.layers.iter() {
+            count = module.output_count(count);
+            values.push(vec![0.0; count]);
+        }
+        InferenceSession {
+            network,
+            input_count,
+            values,
+        }
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.input_count
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.values[self.values.len() - 1].len()
+    }
+
+    pub fn run(&mut self, inputs: &[f32]) -> &[f32] {
+    //Calculates the outputs for the inputs, the same as the last layer of Network::calculate, and returns them without copying.
+        if inputs.len() != self.input_count {
+            panic!("The session was made for {} inputs, but received {}", self.input_count, inputs.len());
+        }
+        self.network.layers[0].forward_into(inputs, &mut self.values[0]);
+        for module_num in 1..self.values.len() {
+            let (previous, next) = self.values.split_at_mut(module_num);
+            self.network.layers[module_num].forward_into(&previous[module_num - 1], &mut next[0]);
+        }
+        &self.values[self.values.len() - 1]
+    }
+
+    pub fn forward_into(&mut self, inputs: &[f32], outputs: &mut [f32]) {
+    //Like run, but writes the outputs into a list of the caller's.
+        if outputs.len() != self.output_count() {
+            panic!("The network gives {} outputs, but there is room for {}", self.output_count(), outputs.len());
+        }
+        outputs.copy_from_slice(self.run(inputs));
+    }
+
+    pub fn values(&self) -> &[Vec<f32>] {
+    //The values of every module from the last call of run or forward_into.
+        &self.values
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::conv::{Conv2d, Flatten, MaxPool2d};
+    use crate::modules::{ActivationLayer, Softmax};
+    use crate::nodes_layers::{Activation, Layer};
+
+    fn check(network: &Network, input_count: usize) {
+        let mut session = InferenceSession::new(network, input_count);
+        let mut outputs = vec![0.0; session.output_count()];
+        for num in 0..5 {
+            let inputs: Vec<f32> = (0..input_count).map(|input_num| ((input_num * 3 + num) % 7) as f32 * 0.3 - 1.0).collect();
+            let values = network.calculate(&inputs);
+            assert_eq!(session.run(&inputs), &values[values.len() - 1][..]);
+            assert_eq!(session.values(), &values[..]);
+            session.forward_into(&inputs, &mut outputs);
+            assert_eq!(outputs, values[values.len() - 1]);
+        }
+    }
+
+    #[test]
+    fn run_gives_the_same_values_as_calculate() {
+        check(&Network::with_seed(vec![4, 8, 8, 9], 0.5, 1), 4);
+
+        let mut network = Network::empty(0.1);
+        network.push(Box::new(Conv2d::new(1, 2, (4, 4), 3, 1, 1)));
+        network.push(Box::new(ActivationLayer::new(Activation::Relu)));
+        network.push(Box::new(MaxPool2d::new(2, (4, 4), 2, 2, 0)));
+        network.push(Box::new(Flatten::new(vec![2, 2, 2])));
+        network.push(Box::new(Layer::with_activation(8, 3, Activation::Identity)));
+        network.push(Box::new(Softmax::new()));
+        check(&network, 16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn the_wrong_number_of_inputs_panics() {
+        let network = Network::with_seed(vec![4, 8, 9], 0.5, 1);
+        InferenceSession::new(&network, 4).run(&[0.0; 3]);
+    }
+}
@@ -0,0 +1,339 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::nodes_layers::{Activation, Cost, Layer, Network};
+
+//Writes training steps of a network in the same layout as Simple_Neural_Net.ods, so the program's numbers can be checked cell by cell.
+//Each row is one training step on the same sample: the inputs, then for each layer the weights of every node (with the bias last),
+//the values and the deltas, and at the end the targets and the cost. The learning rate is in A8 and the first step is in row 8.
+//The flat ODS file (.fods) has the same formulas as the spreadsheet, with the program's numbers as the starting values,
+//so the spreadsheet recalculates everything itself. The CSV file only has the program's numbers.
+
+const FIRST_ROW: usize = 8;
+
+//The inputs and targets the 2-2-2 network of the spreadsheet is trained on.
+pub const EXAMPLE_INPUTS: [f32; 2] = [0.1, 0.9];
+pub const EXAMPLE_TARGETS: [f32; 2] = [0.3, 0.4];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    Number(f32),
+    Formula(String, f32), //A formula without the leading "=", using A1 style references, and the value the program found for it.
+}
+
+pub fn example_network() -> Network {
+//The 2-2-2 network of Simple_Neural_Net.ods and the back propagation note, with the same starting weights.
+    //The weights of each node, with the bias last, as in the lavender boxes of the spreadsheet.
+    let weights = [[[0.5, -0.2, 0.3], [0.2, -0.6, 0.6]], [[0.1, 0.5, -0.3], [0.2, 0.3, 0.5]]];
+    let mut network = Network::empty(0.5);
+    for layer_weights in weights.iter() {
+        let mut layer = Layer::new(2, 2);
+        for (node, node_weights) in layer.nodes.iter_mut().zip(layer_weights.iter()) {
+            node.weights = node_weights[0..2].to_vec();
+            node.bias = node_weights[2];
+        }
+        network.push(Box::new(layer));
+    }
+    network
+}
+
+pub fn save_csv(network: &Network, inputs: &[f32], targets: &[f32], steps: usize, path: &Path) -> io::Result<()> {
+    let rows = worked_example(network, inputs, targets, steps)?;
+    let mut text = String::new();
+    for row in rows.iter() {
+        let cells: Vec<String> = row.iter().map(|cell| match cell {
+            Cell::Empty => String::new(),
+            Cell::Text(text) => format!("\"{}\"", text.replace('"', "\"\"")),
+            Cell::Number(value) | Cell::Formula(_, value) => value.to_string(),
+        }).collect();
+        text.push_str(&cells.join(","));
+        text.push('\n');
+    }
+    fs::write(path, text)
+}
+
+pub fn save_fods(network: &Network, inputs: &[f32], targets: &[f32], steps: usize, path: &Path) -> io::Result<()> {
+//Saves a flat OpenDocument spreadsheet, which LibreOffice opens like an .ods file.
+    let rows = worked_example(network, inputs, targets, steps)?;
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str("<office:document xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\" ");
+    xml.push_str("xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" xmlns:of=\"urn:oasis:names:tc:opendocument:xmlns:of:1.2\" ");
+    xml.push_str("office:version=\"1.2\" office:mimetype=\"application/vnd.oasis.opendocument.spreadsheet\">\n");
+    xml.push_str("<office:body><office:spreadsheet><table:table table:name=\"Sheet1\">\n");
+    for row in rows.iter() {
+        xml.push_str("<table:table-row>");
+        for cell in row.iter() {
+            match cell {
+                Cell::Empty => xml.push_str("<table:table-cell/>"),
+                Cell::Text(text) => xml.push_str(&format!("<table:table-cell office:value-type=\"string\"><text:p>{}</text:p></table:table-cell>", escape(text))),
+                Cell::Number(value) => xml.push_str(&format!("<table:table-cell office:value-type=\"float\" office:value=\"{}\"><text:p>{}</text:p></table:table-cell>", value, value)),
+                Cell::Formula(formula, value) => xml.push_str(&format!("<table:table-cell table:formula=\"of:={}\" office:value-type=\"float\" office:value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+                    escape(&ods_references(formula)), value, value)),
+            }
+        }
+        xml.push_str("</table:table-row>\n");
+    }
+    xml.push_str("</table:table></office:spreadsheet></office:body></office:document>\n");
+    fs::write(path, xml)
+}
+
+pub fn worked_example(network: &Network, inputs: &[f32], targets: &[f32], steps: usize) -> io::Result<Vec<Vec<Cell>>> {
+//The cells of the spreadsheet, row by row. Training is done on a copy, so the network itself isn't changed.
+//Only networks of dense layers with the squared error cost can be written, since those are what the spreadsheet does.
+    let mut layers: Vec<Layer> = Vec::new();
+    for module in network.layers.iter() {
+        match module.as_layer() {
+            Some(layer) => layers.push(layer.clone()),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("The spreadsheet can only show dense layers, not {}", module.describe()))),
+        }
+    }
+    if layers.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "The network has no layers"))
+    }
+    match network.cost {
+        Cost::SquaredError => (),
+        Cost::CrossEntropy => return Err(io::Error::new(io::ErrorKind::InvalidInput, "The spreadsheet only uses the squared error cost")),
+    }
+    if inputs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "At least one input is needed"))
+    }
+    let mut previous_count = inputs.len();
+    for (layer_num, layer) in layers.iter().enumerate() {
+        if let Err(error) = layer.check_shape(previous_count) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Layer {} {}", layer_num + 1, error)))
+        }
+        previous_count = layer.node_count;
+    }
+    if targets.len() != previous_count {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("The network has {} outputs, but {} targets were given", previous_count, targets.len())))
+    }
+
+    //Where each block of columns starts.
+    let input_start = 2;
+    let mut weight_starts = Vec::new();
+    let mut value_starts = Vec::new();
+    let mut delta_starts = Vec::new();
+    let mut column = input_start + inputs.len();
+    for layer in layers.iter() {
+        let previous_count = layer.nodes[0].weights.len();
+        weight_starts.push(column);
+        column += layer.node_count * (previous_count + 1);
+        value_starts.push(column);
+        column += layer.node_count;
+        delta_starts.push(column);
+        column += layer.node_count;
+    }
+    let target_start = column;
+    let cost_column = target_start + targets.len();
+    let width = cost_column + 1;
+    let last = layers.len() - 1;
+    //The column of value num of the layer before layer_num, which is the inputs for the first layer.
+    let previous_value = |layer_num: usize, num: usize| if layer_num == 0 {input_start + num} else {value_starts[layer_num - 1] + num};
+
+    let mut rows = vec![vec![Cell::Empty; width]; FIRST_ROW - 1];
+    rows[0][0] = Cell::Text(format!("Training on the inputs in {}{}:{}{} with the targets in {}{}:{}{}. Each row is one training step.",
+        column_name(input_start), FIRST_ROW, column_name(input_start + inputs.len() - 1), FIRST_ROW,
+        column_name(target_start), FIRST_ROW, column_name(cost_column - 1), FIRST_ROW));
+    rows[1][0] = Cell::Text("Written by simple_nn. The numbers in the first row are the program's, the rest are calculated by the formulas.".to_string());
+    rows[3][input_start] = Cell::Text("Layer 0".to_string());
+    rows[4][input_start] = Cell::Text("Input".to_string());
+    rows[5][0] = Cell::Text("Learning rate".to_string());
+    for num in 0..inputs.len() {
+        rows[5][input_start + num] = Cell::Text(format!("a{}", num));
+    }
+    for (layer_num, layer) in layers.iter().enumerate() {
+        let previous_count = layer.nodes[0].weights.len();
+        rows[3][weight_starts[layer_num]] = Cell::Text(format!("Layer {}", layer_num + 1));
+        for node_num in 0..layer.node_count {
+            for weight_num in 0..=previous_count {
+                rows[5][weight_starts[layer_num] + node_num * (previous_count + 1) + weight_num] = Cell::Text(format!("w{}{}", node_num, weight_num));
+            }
+            rows[5][value_starts[layer_num] + node_num] = Cell::Text(format!("a{}", node_num));
+            rows[5][delta_starts[layer_num] + node_num] = Cell::Text(format!("δ{}", node_num));
+        }
+    }
+    rows[4][value_starts[last]] = Cell::Text("Output".to_string());
+    rows[4][target_start] = Cell::Text("Target".to_string());
+    rows[4][cost_column] = Cell::Text("Cost".to_string());
+    for num in 0..targets.len() {
+        rows[5][target_start + num] = Cell::Text(format!("T{}", num));
+    }
+    rows[5][cost_column] = Cell::Text("C".to_string());
+
+    let mut network = network.clone();
+    for step in 0..steps {
+        let sheet_row = FIRST_ROW + step;
+        let above = sheet_row - 1;
+        let at = |column: usize| format!("{}{}", column_name(column), sheet_row);
+        let previous = |column: usize| format!("{}{}", column_name(column), above);
+        let values = network.calculate(inputs);
+        let deltas = network.find_deltas(inputs, targets);
+        let mut row = vec![Cell::Empty; width];
+        if step == 0 {
+            row[0] = Cell::Number(network.learning_rate);
+        }
+        for num in 0..inputs.len() {
+            row[input_start + num] = if step == 0 {Cell::Number(inputs[num])} else {Cell::Formula(previous(input_start + num), inputs[num])};
+        }
+        for num in 0..targets.len() {
+            row[target_start + num] = if step == 0 {Cell::Number(targets[num])} else {Cell::Formula(previous(target_start + num), targets[num])};
+        }
+        for (layer_num, layer) in layers.iter().enumerate() {
+            let layer_now = network.layers[layer_num].as_layer().unwrap();
+            let previous_count = layer.nodes[0].weights.len();
+            for node_num in 0..layer.node_count {
+                let node = &layer_now.nodes[node_num];
+                let node_start = weight_starts[layer_num] + node_num * (previous_count + 1);
+                for weight_num in 0..=previous_count {
+                    let value = if weight_num < previous_count {node.weights[weight_num]} else {node.bias};
+                    row[node_start + weight_num] = if step == 0 {
+                        Cell::Number(value)
+                    } else if weight_num < previous_count { // The weight of the row above, minus the learning rate times the delta times the value it was multiplied with
+                        Cell::Formula(format!("{}-$A${}*{}*{}", previous(node_start + weight_num), FIRST_ROW, previous(delta_starts[layer_num] + node_num), previous(previous_value(layer_num, weight_num))), value)
+                    } else {
+                        Cell::Formula(format!("{}-$A${}*{}", previous(node_start + weight_num), FIRST_ROW, previous(delta_starts[layer_num] + node_num)), value)
+                    };
+                }
+                let mut sum = String::new();
+                for weight_num in 0..previous_count {
+                    sum.push_str(&format!("{}*{}+", at(previous_value(layer_num, weight_num)), at(node_start + weight_num)));
+                }
+                sum.push_str(&at(node_start + previous_count));
+                row[value_starts[layer_num] + node_num] = Cell::Formula(activation_formula(&layer.activation, &sum), values[layer_num][node_num]);
+
+                let value_cell = at(value_starts[layer_num] + node_num);
+                let derivative = derivative_formula(&layer.activation, &value_cell);
+                let delta = if layer_num == last {
+                    format!("({}-{})*{}", value_cell, at(target_start + node_num), derivative)
+                } else { // The deltas of the next layer, multiplied by the weights leading to them from this node
+                    let next_layer = &layers[layer_num + 1];
+                    let mut terms = Vec::new();
+                    for next_num in 0..next_layer.node_count {
+                        terms.push(format!("{}*{}", at(delta_starts[layer_num + 1] + next_num), at(weight_starts[layer_num + 1] + next_num * (layer.node_count + 1) + node_num)));
+                    }
+                    format!("{}*({})", derivative, terms.join("+"))
+                };
+                row[delta_starts[layer_num] + node_num] = Cell::Formula(delta, deltas[layer_num][node_num]);
+            }
+        }
+        let mut cost_terms = Vec::new();
+        for num in 0..targets.len() {
+            cost_terms.push(format!("0.5*({}-{})^2", at(value_starts[last] + num), at(target_start + num)));
+        }
+        row[cost_column] = Cell::Formula(cost_terms.join("+"), network.find_cost(inputs, targets));
+        rows.push(row);
+        network.find_make_adjust(inputs, targets);
+    }
+    Ok(rows)
+}
+
+fn activation_formula(activation: &Activation, sum: &str) -> String {
+    match activation {
+        Activation::Sigmoid => format!("1/(1+EXP(-({})))", sum),
+        Activation::Tanh => format!("TANH({})", sum),
+        Activation::Relu => format!("MAX(0;{})", sum),
+        Activation::Identity => format!("({})", sum),
+    }
+}
+
+fn derivative_formula(activation: &Activation, value: &str) -> String {
+//The derivative found from the node's value, like Activation::derivative.
+    match activation {
+        Activation::Sigmoid => format!("{}*(1-{})", value, value),
+        Activation::Tanh => format!("(1-{}^2)", value),
+        Activation::Relu => format!("IF({}>0;1;0)", value),
+        Activation::Identity => "1".to_string(),
+    }
+}
+
+pub fn column_name(column: usize) -> String {
+//The spreadsheet's name of a column counted from 0: A, B, ..., Z, AA, AB, ...
+    let mut name = Vec::new();
+    let mut number = column + 1;
+    while number > 0 {
+        name.push((b'A' + ((number - 1) % 26) as u8) as char);
+        number = (number - 1) / 26;
+    }
+    name.iter().rev().collect()
+}
+
+fn ods_references(formula: &str) -> String {
+//OpenDocument formulas write references as [.A1], so every reference in the formula is put in brackets.
+    let characters: Vec<char> = formula.chars().collect();
+    let mut result = String::new();
+    let mut num = 0;
+    while num < characters.len() {
+        let start = num;
+        while num < characters.len() && (characters[num] == '$' || characters[num].is_ascii_uppercase()) {
+            num += 1;
+        }
+        let letters_end = num;
+        while num < characters.len() && (characters[num] == '$' || characters[num].is_ascii_digit()) {
+            num += 1;
+        }
+        let is_reference = letters_end > start && num > letters_end && characters[start..letters_end].iter().any(|c| c.is_ascii_uppercase())
+            && (num >= characters.len() || characters[num] != '(');
+        let text: String = characters[start..num].iter().collect();
+        if is_reference {
+            result.push_str(&format!("[.{}]", text));
+        } else if num > start {
+            result.push_str(&text);
+        } else {
+            result.push(characters[num]);
+            num += 1;
+        }
+    }
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrong_numbers_of_inputs_and_targets_are_errors() {
+        let network = Network::with_seed(vec![2, 2, 2], 0.5, 1);
+        assert!(worked_example(&network, &[0.05, 0.1], &[0.01, 0.99], 3).is_ok());
+        for (inputs, targets) in [(vec![], vec![0.01, 0.99]), (vec![0.05], vec![0.01, 0.99]), (vec![0.05, 0.1, 0.2], vec![0.01, 0.99]),
+                                  (vec![0.05, 0.1], vec![]), (vec![0.05, 0.1], vec![0.01, 0.99, 0.5])].iter() {
+            let error = worked_example(&network, inputs, targets, 3).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn first_step_matches_the_network() {
+        let network = example_network();
+        let path = std::env::temp_dir().join(format!("simple_nn_worked_example_{}.csv", std::process::id()));
+        save_csv(&network, &EXAMPLE_INPUTS, &EXAMPLE_TARGETS, 2, &path).unwrap();
+        let text = fs::read_to_string(&path);
+        fs::remove_file(&path).unwrap();
+        let text = text.unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), FIRST_ROW + 1);
+        let row: Vec<f32> = lines[FIRST_ROW - 1].split(',').map(|cell| if cell.is_empty() {f32::NAN} else {cell.parse().unwrap()}).collect();
+
+        //A8 is the learning rate, C8:D8 the inputs, then for each layer 6 weights, 2 values and 2 deltas, and the targets and the cost at the end.
+        let values = network.calculate(&EXAMPLE_INPUTS);
+        let deltas = network.find_deltas(&EXAMPLE_INPUTS, &EXAMPLE_TARGETS);
+        assert_eq!(row.len(), 27);
+        assert_eq!(row[0], 0.5);
+        assert_eq!(row[2..4].to_vec(), EXAMPLE_INPUTS.to_vec());
+        assert_eq!(row[4..10].to_vec(), vec![0.5, -0.2, 0.3, 0.2, -0.6, 0.6]);
+        assert_eq!(row[10..12].to_vec(), values[0]);
+        assert_eq!(row[12..14].to_vec(), deltas[0]);
+        assert_eq!(row[14..20].to_vec(), vec![0.1, 0.5, -0.3, 0.2, 0.3, 0.5]);
+        assert_eq!(row[20..22].to_vec(), values[1]);
+        assert_eq!(row[22..24].to_vec(), deltas[1]);
+        assert_eq!(row[24..26].to_vec(), EXAMPLE_TARGETS.to_vec());
+        assert_eq!(row[26], network.find_cost(&EXAMPLE_INPUTS, &EXAMPLE_TARGETS));
+    }
+}
@@ -0,0 +1,177 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+
+use crate::nodes_layers::{Activation, Layer, Network, Node};
+use crate::rng::SimpleRng;
+
+//Networks of dense layers whose sizes are part of the type, so a wrong number of inputs or weights is found by the compiler
+//instead of by a panic at runtime. The values are kept in arrays on the stack, so calculate allocates nothing.
+//They calculate exactly the same values as a Network with the same weights, and can be converted to one for training and back.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StaticLayer<const IN: usize, const OUT: usize> {
+//A dense layer with IN inputs and OUT nodes.
+    pub weights: [[f32; IN]; OUT], //weights[node] are the weights of the node's connections to the previous layer.
+    pub biases: [f32; OUT],
+    pub activation: Activation,
+}
+
+impl<const IN: usize, const OUT: usize> StaticLayer<IN, OUT> {
+    pub fn from_rng(activation: Activation, rng: &mut SimpleRng) -> StaticLayer<IN, OUT> {
+    //Random biases and weights in [-1; 1[, drawn in the same order as Layer::from_rng, so the same rng gives the same layer.
+        let mut weights = [[0.0; IN]; OUT];
+        let mut biases = [0.0; OUT];
+        for node_num in 0..OUT {
+            for weight_num in 0..IN {
+                weights[node_num][weight_num] = rng.gen_range(-1.0, 1.0);
+            }
+            biases[node_num] = rng.gen_range(-1.0, 1.0);
+        }
+        StaticLayer {
+            weights,
+            biases,
+            activation,
+        }
+    }
+
+    pub fn from_layer(layer: &Layer) -> Option<StaticLayer<IN, OUT>> {
+    //Copies the weights and biases of a Layer. Returns None if it doesn't have OUT nodes with IN weights each.
+        if layer.node_count != OUT || layer.nodes.len() != OUT {
+            return None
+        }
+        let mut weights = [[0.0; IN]; OUT];
+        let mut biases = [0.0; OUT];
+        for node_num in 0..OUT {
+            let node = &layer.nodes[node_num];
+            if node.weights.len() != IN {
+                return None
+            }
+            weights[node_num].copy_from_slice(&node.weights);
+            biases[node_num] = node.bias;
+        }
+        Some(StaticLayer {
+            weights,
+            biases,
+            activation: layer.activation,
+        })
+    }
+
+    pub fn to_layer(&self) -> Layer {
+        let mut nodes = Vec::new();
+        for node_num in 0..OUT {
+            nodes.push(Node {
+                bias: self.biases[node_num],
+                weights: self.weights[node_num].to_vec(),
+                bias_adjust: None,
+                weight_adjusts: Vec::new(),
+            });
+        }
+        Layer {
+            nodes,
+            node_count: OUT,
+            activation: self.activation,
+        }
+    }
+
+    pub fn calculate(&self, previous_layer_values: &[f32; IN]) -> [f32; OUT] {
+    //The same as Layer::calculate: the bias plus the values multiplied by the weights, added in the same order, through the activation function.
+        let mut values = [0.0; OUT];
+        for node_num in 0..OUT {
+            let mut value = self.biases[node_num];
+            for pos_num in 0..IN {
+                value += previous_layer_values[pos_num] * self.weights[node_num][pos_num];
+            }
+            values[node_num] = self.activation.apply(value);
+        }
+        values
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StaticNetwork<const IN: usize, const H1: usize, const H2: usize, const OUT: usize> {
+//IN inputs, two hidden layers of H1 and H2 nodes, and OUT outputs, like the 4-8-8-9 network of the 7-segment task.
+    pub hidden1: StaticLayer<IN, H1>,
+    pub hidden2: StaticLayer<H1, H2>,
+    pub output: StaticLayer<H2, OUT>,
+}
+
+impl<const IN: usize, const H1: usize, const H2: usize, const OUT: usize> StaticNetwork<IN, H1, H2, OUT> {
+    pub fn with_seed(seed: u64) -> StaticNetwork<IN, H1, H2, OUT> {
+    //Sigmoid layers with the same weights and biases as Network::with_seed(vec![IN, H1, H2, OUT], _, seed).
+        let mut rng = SimpleRng::new(seed);
+        let hidden1 = StaticLayer::from_rng(Activation::Sigmoid, &mut rng);
+        let hidden2 = StaticLayer::from_rng(Activation::Sigmoid, &mut rng);
+        let output = StaticLayer::from_rng(Activation::Sigmoid, &mut rng);
+        StaticNetwork {
+            hidden1,
+            hidden2,
+            output,
+        }
+    }
+
+    pub fn from_network(network: &Network) -> Option<StaticNetwork<IN, H1, H2, OUT>> {
+    //Copies a Network of exactly three dense layers with these sizes. Returns None for any other network.
+        if network.layers.len() != 3 {
+            return None
+        }
+        Some(StaticNetwork {
+            hidden1: StaticLayer::from_layer(network.layers[0].as_layer()?)?,
+            hidden2: StaticLayer::from_layer(network.layers[1].as_layer()?)?,
+            output: StaticLayer::from_layer(network.layers[2].as_layer()?)?,
+        })
+    }
+
+    pub fn to_network(&self, learning_rate: f32) -> Network {
+    //A Network with the same layers, e.g. for training it further.
+        let mut network = Network::empty(learning_rate);
+        network.push(Box::new(self.hidden1.to_layer()));
+        network.push(Box::new(self.hidden2.to_layer()));
+        network.push(Box::new(self.output.to_layer()));
+        network
+    }
+
+    pub fn calculate(&self, inputs: &[f32; IN]) -> [f32; OUT] {
+    //The outputs, which are the same as the last layer of Network::calculate.
+        self.values(inputs).2
+    }
+
+    pub fn values(&self, inputs: &[f32; IN]) -> ([f32; H1], [f32; H2], [f32; OUT]) {
+    //The values of all three layers, like Network::calculate gives them.
+        let hidden1 = self.hidden1.calculate(inputs);
+        let hidden2 = self.hidden2.calculate(&hidden1);
+        let output = self.output.calculate(&hidden2);
+        (hidden1, hidden2, output)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outputs_are_the_same_as_the_network() {
+        let mut network = Network::with_seed(vec![3, 5, 4, 2], 0.5, 11);
+        let seeded: StaticNetwork<3, 5, 4, 2> = StaticNetwork::with_seed(11);
+        assert_eq!(StaticNetwork::from_network(&network), Some(seeded));
+        for _ in 0..20 {
+            network.find_make_adjust(&[0.2, -0.7, 1.0], &[0.9, 0.1]);
+        }
+        let fixed: StaticNetwork<3, 5, 4, 2> = StaticNetwork::from_network(&network).unwrap();
+        for num in 0..10 {
+            let inputs = [num as f32 * 0.3 - 1.5, 0.5 - num as f32 * 0.1, (num % 3) as f32];
+            let (hidden1, hidden2, output) = fixed.values(&inputs);
+            let values = network.calculate(&inputs);
+            assert_eq!(values, vec![hidden1.to_vec(), hidden2.to_vec(), output.to_vec()]);
+            assert_eq!(fixed.calculate(&inputs), output);
+        }
+        assert_eq!(StaticNetwork::from_network(&fixed.to_network(0.5)), Some(fixed));
+    }
+
+    #[test]
+    fn networks_of_other_sizes_are_none() {
+        let network = Network::with_seed(vec![3, 5, 4, 2], 0.5, 11);
+        assert_eq!(StaticNetwork::<3, 5, 4, 3>::from_network(&network), None);
+        assert_eq!(StaticNetwork::<2, 5, 4, 2>::from_network(&network), None);
+        assert_eq!(StaticNetwork::<3, 5, 4, 2>::from_network(&Network::with_seed(vec![3, 5, 2], 0.5, 11)), None);
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::layout::{seven_segment_display, NetworkLayout};
+use crate::nodes_layers::Network;
+
+//Draws a network as an SVG image, the same way the visualizer draws it, but without needing a window or a GPU.
+//The layout works with y pointing up, so every y is flipped before it is written.
+
+pub fn save_network_svg(network: &Network, inputs: &[f32], path: &Path, width: f32, height: f32) -> io::Result<()> {
+    fs::write(path, network_svg(network, inputs, width, height))
+}
+
+pub fn network_svg(network: &Network, inputs: &[f32], width: f32, height: f32) -> String {
+//The network with the given inputs. Weights are lines, blue for positive and red for negative, thicker and less transparent the larger they are.
+//Nodes are filled from black to white by their values. If the network has 9 outputs, they are also shown on a 7-segment display.
+    let results = network.calculate(inputs);
+    let mut sizes = vec![inputs.len()];
+    for values in results.iter() {
+        sizes.push(values.len());
+    }
+    let shows_display = results[results.len() - 1].len() == 9;
+    let margin = 20.0;
+    let network_width = if shows_display {width * 0.7} else {width};
+    let layout = NetworkLayout::new(&sizes, margin, margin, network_width - 2.0 * margin, height - 2.0 * margin);
+    let flip = |position: (f32, f32)| (position.0, height - position.1);
+
+    let mut svg = String::new();
+    svg.push_str(&format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n", width, height, width, height));
+    svg.push_str(&format!("<rect width=\"{}\" height=\"{}\" fill=\"black\"/>\n", width, height));
+
+    for (layer_num, module) in network.layers.iter().enumerate() { // The weights are drawn first, so the nodes are drawn on top of the lines
+        let layer = match module.as_layer() {
+            Some(layer) => layer,
+            None => continue,
+        };
+        for (node_num, node) in layer.nodes.iter().enumerate() {
+            for (weight_num, weight) in node.weights.iter().enumerate() {
+                let strength = (weight.abs() / 4.0).min(1.0); // Weights above 4 are drawn as if they were 4
+                let color = if *weight > 0.0 {(0.2, 0.5, 1.0)} else {(1.0, 0.25, 0.2)};
+                let start = flip(layout.columns[layer_num][weight_num]);
+                let end = flip(layout.columns[layer_num + 1][node_num]);
+                svg.push_str(&format!("<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{:.2}\"/>\n",
+                    start.0, start.1, end.0, end.1, rgb(color.0, color.1, color.2), 0.15 + 0.85 * strength, 0.5 + 3.5 * strength));
+            }
+        }
+    }
+
+    for (column_num, column) in layout.columns.iter().enumerate() {
+        let values = if column_num == 0 {inputs} else {&results[column_num - 1]};
+        for (node_num, position) in column.iter().enumerate() {
+            let value = values[node_num].clamp(0.0, 1.0);
+            let (x, y) = flip(*position);
+            svg.push_str(&format!("<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"{}\" stroke=\"{}\"/>\n",
+                x, y, layout.node_radius, rgb(value, value, value), rgb(0.4, 0.4, 0.4)));
+        }
+    }
+
+    if shows_display {
+        let display_height = (height * 0.4).min(width * 0.2);
+        let polygons = seven_segment_display(width * 0.85, height / 2.0, display_height);
+        let outputs = &results[results.len() - 1];
+        for (num, polygon) in polygons.iter().enumerate() {
+            let value = outputs[num].clamp(0.0, 1.0);
+            let shade = 0.12 + 0.88 * value; // Segments that are off are dark gray, so the shape of the display can still be seen
+            let points: Vec<String> = polygon.iter().map(|position| {
+                let (x, y) = flip(*position);
+                format!("{:.2},{:.2}", x, y)
+            }).collect();
+            svg.push_str(&format!("<polygon points=\"{}\" fill=\"{}\"/>\n", points.join(" "), rgb(shade, shade, shade)));
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn rgb(red: f32, green: f32, blue: f32) -> String {
+    let byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", byte(red), byte(green), byte(blue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_every_node_weight_and_segment() {
+        let network = Network::with_seed(vec![4, 8, 8, 9], 0.5, 3);
+        let svg = network_svg(&network, &[1.0, 0.0, 0.0, 1.0], 700.0, 500.0);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<circle ").count(), 4 + 8 + 8 + 9);
+        assert_eq!(svg.matches("<line ").count(), 4 * 8 + 8 * 8 + 8 * 9);
+        assert_eq!(svg.matches("<polygon ").count(), 9);
+
+        let network = Network::with_seed(vec![2, 3, 1], 0.5, 3); // Without 9 outputs there is no display
+        let svg = network_svg(&network, &[0.5, 0.5], 300.0, 200.0);
+        assert_eq!(svg.matches("<circle ").count(), 2 + 3 + 1);
+        assert_eq!(svg.matches("<line ").count(), 2 * 3 + 3);
+        assert_eq!(svg.matches("<polygon ").count(), 0);
+    }
+}
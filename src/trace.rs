@@ -0,0 +1,271 @@
+use std::io;
+
+use crate::nodes_layers::{Layer, Network};
+
+//A record of everything that happens in one training step on one sample, for following the back propagation note with real numbers.
+//The names follow the note: layer (0) is the inputs, h is a node's weighted sum before the activation function, a is its value,
+//δ is how the cost changes with h, and w_ji is the weight from node i of the previous layer to node j, with the bias as the last weight.
+
+#[derive(Clone, Debug)]
+pub struct LayerTrace {
+    pub weighted_sums: Vec<f32>, //h_j
+    pub values: Vec<f32>, //a_j
+    pub deltas: Vec<f32>, //δ_j
+    pub weights: Vec<Vec<f32>>, //weights[j][i] before the step. The last one of each node is the bias.
+    pub gradients: Vec<Vec<f32>>, //dC/dw_ji = δ_j * a_i of the previous layer, which is δ_j itself for the bias.
+    pub new_weights: Vec<Vec<f32>>, //w_ji - learning_rate * dC/dw_ji
+}
+
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub inputs: Vec<f32>,
+    pub targets: Vec<f32>,
+    pub learning_rate: f32,
+    pub cost: f32, //Before the step.
+    pub layers: Vec<LayerTrace>, //layers[0] is layer (1).
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TraceFormat {
+    Text,
+    Markdown,
+    Latex,
+}
+
+impl Network {
+    pub fn trace_make_adjust(&mut self, inputs: &[f32], desired_outputs: &[f32]) -> io::Result<Trace> {
+    //Does the same as find_make_adjust, and records every number on the way. Only works for networks of dense layers.
+        let mut weights = Vec::new();
+        let mut previous_count = inputs.len();
+        for (layer_num, layer) in dense_layers(self)?.iter().enumerate() {
+            if let Err(error) = layer.check_shape(previous_count) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Layer {} {}", layer_num + 1, error)))
+            }
+            if layer.nodes.iter().any(|node| node.bias_adjust.is_some()) {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "The network has adjusts that haven't been used yet, so the gradients of one sample can't be found"))
+            }
+            previous_count = layer.node_count;
+            weights.push(node_weights(layer));
+        }
+        if weights.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "The network has no layers"))
+        }
+        if desired_outputs.len() != previous_count {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("The network has {} outputs, but {} targets were given", previous_count, desired_outputs.len())))
+        }
+        let cost = self.find_cost(inputs, desired_outputs);
+        let values = self.calculate(inputs);
+        self.find_adjusts(inputs, desired_outputs);
+
+        let mut layers = Vec::new();
+        for (layer_num, layer) in dense_layers(self)?.iter().enumerate() {
+            let previous_values = if layer_num == 0 {inputs} else {&values[layer_num - 1]};
+            let mut gradients = Vec::new();
+            let mut deltas = Vec::new();
+            for node in layer.nodes.iter() {
+                //find_adjusts has just set the bias adjust of every node, which is its delta.
+                let delta = node.bias_adjust.unwrap_or(0.0);
+                let mut node_gradients = node.weight_adjusts.clone();
+                node_gradients.push(delta);
+                gradients.push(node_gradients);
+                deltas.push(delta);
+            }
+            layers.push(LayerTrace {
+                weighted_sums: layer.nodes.iter().map(|node| node.weighted_sum(previous_values)).collect(),
+                values: values[layer_num].clone(),
+                deltas,
+                weights: weights[layer_num].clone(),
+                gradients,
+                new_weights: Vec::new(),
+            });
+        }
+        self.adjust();
+        for (layer_num, layer) in dense_layers(self)?.iter().enumerate() {
+            layers[layer_num].new_weights = node_weights(layer);
+        }
+        Ok(Trace {
+            inputs: inputs.to_vec(),
+            targets: desired_outputs.to_vec(),
+            learning_rate: self.learning_rate,
+            cost,
+            layers,
+        })
+    }
+}
+
+fn dense_layers(network: &Network) -> io::Result<Vec<&Layer>> {
+    let mut layers = Vec::new();
+    for module in network.layers.iter() {
+        match module.as_layer() {
+            Some(layer) => layers.push(layer),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Only dense layers can be traced, not {}", module.describe()))),
+        }
+    }
+    Ok(layers)
+}
+
+fn node_weights(layer: &Layer) -> Vec<Vec<f32>> {
+//The weights of each node followed by its bias, in the order of the note.
+    let mut weights = Vec::new();
+    for node in layer.nodes.iter() {
+        let mut node_weights = node.weights.clone();
+        node_weights.push(node.bias);
+        weights.push(node_weights);
+    }
+    weights
+}
+
+impl Trace {
+    pub fn render(&self, format: TraceFormat) -> String {
+    //A table of the nodes and a table of the weights for each layer, as plain text, Markdown or LaTeX.
+        let mut text = String::new();
+        let inputs: Vec<String> = self.inputs.iter().map(|value| number(*value)).collect();
+        let targets: Vec<String> = self.targets.iter().map(|value| number(*value)).collect();
+        text.push_str(&paragraph(format, &format!("Inputs {} = ({}), targets {} = ({}), learning rate {}, cost {} = {}",
+            symbol(format, "a", None, Some(0)), inputs.join(", "), symbol(format, "T", None, None), targets.join(", "),
+            number(self.learning_rate), symbol(format, "C", None, None), number(self.cost))));
+        for (layer_num, layer) in self.layers.iter().enumerate() {
+            let level = layer_num + 1;
+            let previous_count = layer.weights[0].len() - 1;
+            text.push_str(&paragraph(format, &format!("Layer ({})", level)));
+            let headers = vec!["j".to_string(), symbol(format, "h", Some("j".to_string()), Some(level)), symbol(format, "a", Some("j".to_string()), Some(level)),
+                symbol(format, "δ", Some("j".to_string()), Some(level))];
+            let mut rows = Vec::new();
+            for node_num in 0..layer.values.len() {
+                rows.push(vec![node_num.to_string(), number(layer.weighted_sums[node_num]), number(layer.values[node_num]), number(layer.deltas[node_num])]);
+            }
+            text.push_str(&table(format, &headers, &rows));
+
+            let headers = vec!["weight".to_string(), "before".to_string(), format!("multiplied with {}", symbol(format, "a", Some("i".to_string()), Some(level - 1))),
+                gradient_symbol(format, level), "after".to_string()];
+            let mut rows = Vec::new();
+            for node_num in 0..layer.values.len() {
+                let previous_values = if layer_num == 0 {&self.inputs} else {&self.layers[layer_num - 1].values};
+                for weight_num in 0..=previous_count {
+                    let multiplied = if weight_num < previous_count {number(previous_values[weight_num])} else {"1 (bias)".to_string()};
+                    rows.push(vec![symbol(format, "w", Some(format!("{}{}", node_num, weight_num)), Some(level)), number(layer.weights[node_num][weight_num]),
+                        multiplied, number(layer.gradients[node_num][weight_num]), number(layer.new_weights[node_num][weight_num])]);
+                }
+            }
+            text.push_str(&table(format, &headers, &rows));
+        }
+        text
+    }
+}
+
+fn number(value: f32) -> String {
+    format!("{:.6}", value)
+}
+
+fn symbol(format: TraceFormat, name: &str, lower: Option<String>, layer: Option<usize>) -> String {
+//Writes e.g. a_0^(1) the way the format shows it best.
+    match format {
+        TraceFormat::Text => {
+            let mut text = name.to_string();
+            if let Some(lower) = lower {
+                text.push_str(&lower);
+            }
+            if let Some(layer) = layer {
+                text.push_str(&format!("({})", layer));
+            }
+            text
+        },
+        TraceFormat::Markdown | TraceFormat::Latex => {
+            let name = if name == "δ" {"\\delta"} else {name};
+            let mut text = format!("${}", name);
+            if let Some(lower) = lower {
+                text.push_str(&format!("_{{{}}}", lower));
+            }
+            if let Some(layer) = layer {
+                text.push_str(&format!("^{{({})}}", layer));
+            }
+            text.push('$');
+            text
+        },
+    }
+}
+
+fn gradient_symbol(format: TraceFormat, layer: usize) -> String {
+    match format {
+        TraceFormat::Text => format!("dC/dwji({})", layer),
+        TraceFormat::Markdown | TraceFormat::Latex => format!("$\\frac{{dC}}{{dw_{{ji}}^{{({})}}}}$", layer),
+    }
+}
+
+fn paragraph(format: TraceFormat, text: &str) -> String {
+    match format {
+        TraceFormat::Text => format!("{}\n\n", text),
+        TraceFormat::Markdown => format!("**{}**\n\n", text),
+        TraceFormat::Latex => format!("\\paragraph{{}} {}\n\n", text),
+    }
+}
+
+fn table(format: TraceFormat, headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut text = String::new();
+    match format {
+        TraceFormat::Text => { // Columns padded to the widest cell
+            let mut widths: Vec<usize> = headers.iter().map(|header| header.chars().count()).collect();
+            for row in rows.iter() {
+                for (num, cell) in row.iter().enumerate() {
+                    widths[num] = widths[num].max(cell.chars().count());
+                }
+            }
+            let line = |cells: &[String]| {
+                let padded: Vec<String> = cells.iter().enumerate().map(|(num, cell)| format!("{}{}", cell, " ".repeat(widths[num] - cell.chars().count()))).collect();
+                format!("{}\n", padded.join("  ").trim_end())
+            };
+            text.push_str(&line(headers));
+            text.push_str(&line(&widths.iter().map(|width| "-".repeat(*width)).collect::<Vec<String>>()));
+            for row in rows.iter() {
+                text.push_str(&line(row));
+            }
+        },
+        TraceFormat::Markdown => {
+            text.push_str(&format!("| {} |\n", headers.join(" | ")));
+            text.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+            for row in rows.iter() {
+                text.push_str(&format!("| {} |\n", row.join(" | ")));
+            }
+        },
+        TraceFormat::Latex => {
+            text.push_str(&format!("\\begin{{tabular}}{{{}}}\n", "r".repeat(headers.len())));
+            text.push_str(&format!("{} \\\\\n\\hline\n", headers.join(" & ")));
+            for row in rows.iter() {
+                text.push_str(&format!("{} \\\\\n", row.join(" & ")));
+            }
+            text.push_str("\\end{tabular}\n");
+        },
+    }
+    text.push('\n');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::Softmax;
+
+    #[test]
+    fn trace_takes_the_same_step_as_find_make_adjust() {
+        let mut traced = Network::with_seed(vec![2, 3, 2], 0.5, 8);
+        let mut network = traced.clone();
+        let trace = traced.trace_make_adjust(&[0.1, 0.9], &[0.3, 0.4]).unwrap();
+        network.find_make_adjust(&[0.1, 0.9], &[0.3, 0.4]);
+        for layer_num in 0..network.layer_count {
+            assert_eq!(traced.layers[layer_num].parameters(), network.layers[layer_num].parameters());
+            assert_eq!(trace.layers[layer_num].new_weights, node_weights(network.layers[layer_num].as_layer().unwrap()));
+        }
+    }
+
+    #[test]
+    fn networks_that_cant_be_traced_are_errors() {
+        let mut network = Network::with_seed(vec![2, 3, 2], 0.5, 8);
+        assert!(network.trace_make_adjust(&[0.1], &[0.3, 0.4]).is_err());
+        assert!(network.trace_make_adjust(&[0.1, 0.9], &[0.3]).is_err());
+        network.find_adjusts(&[0.1, 0.9], &[0.3, 0.4]);
+        assert!(network.trace_make_adjust(&[0.1, 0.9], &[0.3, 0.4]).is_err());
+        network.adjust();
+        network.push(Box::new(Softmax::new()));
+        assert!(network.trace_make_adjust(&[0.1, 0.9], &[0.3, 0.4]).is_err());
+    }
+}